@@ -0,0 +1,376 @@
+use crate::blocks;
+use crate::config::{Ack, Escalation, Reminder};
+use crate::scheduler::Context;
+use crate::slack::{SlackClient, SlackMessage};
+use crate::state::{PendingAck, PostRef};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Start waiting for acknowledgement of an occurrence that was posted as
+/// `posts`, escalating step by step until someone acknowledges it.
+pub async fn track(
+    reminder: &Reminder,
+    ack: &Ack,
+    text: &str,
+    blocks: Option<&Value>,
+    posts: Vec<PostRef>,
+    context: &Arc<Context>,
+) {
+    let pending = PendingAck {
+        id: 0,
+        reminder: reminder.name.clone(),
+        workspace: reminder.workspace.clone(),
+        text: text.to_string(),
+        blocks: blocks.cloned(),
+        posts,
+        reactions: ack.reactions.clone(),
+        escalate: ack.escalate.clone(),
+        within_minutes: ack.within.num_minutes(),
+        posted_at: Utc::now(),
+        next_step: 0,
+    };
+    match context.state.add_pending_ack(pending).await {
+        Ok((id, superseded)) => {
+            for old in superseded {
+                println!(
+                    "'{}' posted at {} was never acknowledged; superseded by the new post",
+                    old.reminder, old.posted_at
+                );
+            }
+            println!(
+                "'{}' needs acknowledgement within {}m",
+                reminder.name,
+                ack.within.num_minutes()
+            );
+            tokio::spawn(escalate(id, Arc::clone(context)));
+        }
+        Err(e) => eprintln!(
+            "Failed to record pending acknowledgement of '{}': {}",
+            reminder.name, e
+        ),
+    }
+}
+
+/// Pick up escalations that were waiting before a restart.
+pub fn resume(context: &Arc<Context>) {
+    for pending in context.state.pending_acks() {
+        if pending.next_step < pending.escalate.len() {
+            println!(
+                "'{}' posted at {} is still unacknowledged; resuming escalation at step {}/{}",
+                pending.reminder,
+                pending.posted_at,
+                pending.next_step + 1,
+                pending.escalate.len()
+            );
+            tokio::spawn(escalate(pending.id, Arc::clone(context)));
+        }
+    }
+}
+
+/// Acknowledge the occurrence posted as (`channel`, `ts`), if one is
+/// pending. `reaction` is the emoji name for reactions, `None` for the
+/// "Done" button; reactions only count if the reminder accepts them.
+pub async fn acknowledge(
+    context: &Context,
+    channel: &str,
+    ts: &str,
+    user: &str,
+    reaction: Option<&str>,
+) {
+    let accepts = |pending: &PendingAck| {
+        reaction.is_none_or(|reaction| {
+            pending.reactions.is_empty() || pending.reactions.iter().any(|r| r == reaction)
+        })
+    };
+    match context.state.acknowledge(channel, ts, accepts).await {
+        Ok(Some(pending)) => println!(
+            "'{}' posted at {} acknowledged by {} with {} after {}m",
+            pending.reminder,
+            pending.posted_at,
+            user,
+            reaction.map_or("the Done button".to_string(), |r| format!(":{}:", r)),
+            (Utc::now() - pending.posted_at).num_minutes()
+        ),
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to record acknowledgement in {}: {}", channel, e),
+    }
+}
+
+/// Run the escalation steps of a pending acknowledgement, one every
+/// `within_minutes` after the post, stopping once it is acknowledged.
+async fn escalate(id: u64, context: Arc<Context>) {
+    loop {
+        let Some(pending) = context.state.pending_ack(id) else {
+            return;
+        };
+        let Some(step) = pending.escalate.get(pending.next_step) else {
+            println!(
+                "'{}' posted at {} is still unacknowledged after every escalation step",
+                pending.reminder, pending.posted_at
+            );
+            return;
+        };
+        let steps_in = pending.next_step as i32 + 1;
+        let due = step_due(&pending);
+        let wait = (due - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = context.shutdown.cancelled() => return,
+            _ = tokio::time::sleep(wait) => {}
+        }
+        // Acknowledged while waiting
+        if context.state.pending_ack(id).is_none() {
+            return;
+        }
+
+        let Some(client) = context.clients.get(&pending.workspace) else {
+            eprintln!(
+                "Cannot escalate '{}': no workspace '{}'",
+                pending.reminder, pending.workspace
+            );
+            return;
+        };
+        println!(
+            "'{}' not acknowledged after {}m; escalation step {}/{}: {}",
+            pending.reminder,
+            pending.within_minutes * i64::from(steps_in),
+            steps_in,
+            pending.escalate.len(),
+            step
+        );
+        let reposts = run_step(step, &pending, client, &context).await;
+        match context
+            .state
+            .advance_pending_ack(id, pending.next_step + 1, reposts)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                eprintln!(
+                    "Failed to record escalation of '{}': {}",
+                    pending.reminder, e
+                );
+                return;
+            }
+        }
+    }
+}
+
+/// Carry out one step. Returns the new posts that can be acknowledged.
+async fn run_step(
+    step: &Escalation,
+    pending: &PendingAck,
+    client: &SlackClient,
+    context: &Context,
+) -> Vec<PostRef> {
+    let posted_at = format!(
+        "<!date^{}^{{date_short_pretty}} at {{time}}|{}>",
+        pending.posted_at.timestamp(),
+        pending.posted_at.to_rfc2822()
+    );
+    let mut reposts = Vec::new();
+    match step {
+        Escalation::Repost => {
+            for channel in channels(pending) {
+                let message = SlackMessage {
+                    channel,
+                    text: &pending.text,
+                    blocks: pending.blocks.as_ref(),
+                    attachments: None,
+                    thread_ts: None,
+                    reply_broadcast: None,
+                };
+                reposts.extend(post(&pending.reminder, &message, client, context).await);
+            }
+        }
+        Escalation::Dm(user) => {
+            let label = format!("{} escalation to {}", pending.reminder, user);
+            let channel = match context.retry.run(&label, || client.open_dm(user)).await {
+                Ok(channel) => channel,
+                Err(e) => {
+                    eprintln!("Failed to open DM with {}: {}", user, e);
+                    return reposts;
+                }
+            };
+            let note = format!(
+                ":rotating_light: Nobody has acknowledged '{}', posted {}.",
+                pending.reminder, posted_at
+            );
+            let mut blocks =
+                vec![json!({ "type": "section", "text": { "type": "mrkdwn", "text": note } })];
+            // The original post with its buttons, if the note leaves room
+            let original = pending.blocks.as_ref().and_then(Value::as_array);
+            if let Some(original) = original.filter(|b| b.len() < blocks::MAX_BLOCKS) {
+                blocks.extend(original.iter().cloned());
+            }
+            let blocks = Value::Array(blocks);
+            let message = SlackMessage {
+                channel: &channel,
+                text: &note,
+                blocks: Some(&blocks),
+                attachments: None,
+                thread_ts: None,
+                reply_broadcast: None,
+            };
+            reposts.extend(post(&pending.reminder, &message, client, context).await);
+        }
+        Escalation::Usergroup(group) => {
+            let text = format!(
+                "<!subteam^{}> '{}', posted {}, still needs acknowledging.",
+                group, pending.reminder, posted_at
+            );
+            let mut pinged_in = 0;
+            // Mention the group under the first post in each channel; DMs
+            // (D…) are skipped as the group would not see them.
+            for channel in channels(pending).filter(|channel| !channel.starts_with('D')) {
+                let Some(first) = pending.posts.iter().find(|post| post.channel == channel) else {
+                    continue;
+                };
+                let message = SlackMessage {
+                    channel,
+                    text: &text,
+                    blocks: None,
+                    attachments: None,
+                    thread_ts: Some(first.thread_root()),
+                    reply_broadcast: Some(true),
+                };
+                post(&pending.reminder, &message, client, context).await;
+                pinged_in += 1;
+            }
+            if pinged_in == 0 {
+                eprintln!(
+                    "No channel to ping user group {} about '{}' in",
+                    group, pending.reminder
+                );
+            }
+        }
+    }
+    reposts
+}
+
+/// When the next step runs: one `within_minutes` window per step after
+/// the post.
+fn step_due(pending: &PendingAck) -> DateTime<Utc> {
+    let steps_in = pending.next_step as i32 + 1;
+    pending.posted_at + chrono::Duration::minutes(pending.within_minutes) * steps_in
+}
+
+/// The channels an occurrence was posted in, each once, in posting order.
+fn channels(pending: &PendingAck) -> impl Iterator<Item = &str> {
+    pending
+        .posts
+        .iter()
+        .enumerate()
+        .filter(|(index, post)| {
+            !pending.posts[..*index]
+                .iter()
+                .any(|earlier| earlier.channel == post.channel)
+        })
+        .map(|(_, post)| post.channel.as_str())
+}
+
+async fn post(
+    reminder: &str,
+    message: &SlackMessage<'_>,
+    client: &SlackClient,
+    context: &Context,
+) -> Option<PostRef> {
+    let label = format!("{} escalation in {}", reminder, message.channel);
+    match context
+        .retry
+        .run(&label, || client.post_message(message))
+        .await
+    {
+        Ok(response) => {
+            let (Some(channel), Some(ts)) = (response.data.channel, response.data.ts) else {
+                return None;
+            };
+            Some(PostRef {
+                channel,
+                ts,
+                thread_ts: message.thread_ts.map(str::to_string),
+                posted_at: Utc::now(),
+            })
+        }
+        Err(e) => {
+            eprintln!(
+                "Failed to escalate '{}' in {}: {}",
+                reminder, message.channel, e
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn post(channel: &str, ts: &str) -> PostRef {
+        PostRef {
+            channel: channel.to_string(),
+            ts: ts.to_string(),
+            thread_ts: None,
+            posted_at: Utc.with_ymd_and_hms(2026, 1, 5, 9, 0, 0).unwrap(),
+        }
+    }
+
+    fn pending(posts: Vec<PostRef>, next_step: usize) -> PendingAck {
+        PendingAck {
+            id: 1,
+            reminder: "standup".to_string(),
+            workspace: "default".to_string(),
+            text: "Standup".to_string(),
+            blocks: None,
+            posts,
+            reactions: Vec::new(),
+            escalate: vec![Escalation::Repost, Escalation::Repost],
+            within_minutes: 30,
+            posted_at: Utc.with_ymd_and_hms(2026, 1, 5, 9, 0, 0).unwrap(),
+            next_step,
+        }
+    }
+
+    #[test]
+    fn parses_escalation_steps() {
+        #[derive(serde::Deserialize)]
+        struct Steps {
+            escalate: Vec<Escalation>,
+        }
+        let source =
+            r#"escalate = ["repost", { dm = "U0123456789" }, { usergroup = "S0123456789" }]"#;
+        let steps: Steps = toml::from_str(source).unwrap();
+        assert_eq!(
+            steps.escalate,
+            [
+                Escalation::Repost,
+                Escalation::Dm("U0123456789".to_string()),
+                Escalation::Usergroup("S0123456789".to_string()),
+            ]
+        );
+        assert!(toml::from_str::<Steps>(r#"escalate = ["page"]"#).is_err());
+        assert!(toml::from_str::<Steps>(r#"escalate = [{ email = "U1" }]"#).is_err());
+    }
+
+    #[test]
+    fn runs_one_step_per_window() {
+        let at = |hour, minute| Utc.with_ymd_and_hms(2026, 1, 5, hour, minute, 0).unwrap();
+        assert_eq!(step_due(&pending(Vec::new(), 0)), at(9, 30));
+        assert_eq!(step_due(&pending(Vec::new(), 1)), at(10, 0));
+    }
+
+    #[test]
+    fn lists_each_channel_once_in_posting_order() {
+        let posts = vec![
+            post("C2", "1.1"),
+            post("C1", "1.2"),
+            post("C2", "2.1"),
+            post("D1", "1.3"),
+            post("C1", "2.2"),
+        ];
+        let pending = pending(posts, 0);
+        assert_eq!(channels(&pending).collect::<Vec<_>>(), ["C2", "C1", "D1"]);
+    }
+}
@@ -0,0 +1,304 @@
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+/// Slack's documented limits for `chat.postMessage`.
+pub const MAX_BLOCKS: usize = 50;
+const MAX_ATTACHMENTS: usize = 100;
+const MAX_BLOCK_ID_LEN: usize = 255;
+pub const MAX_SECTION_TEXT_LEN: usize = 3000;
+const MAX_SECTION_FIELDS: usize = 10;
+const MAX_FIELD_TEXT_LEN: usize = 2000;
+const MAX_HEADER_TEXT_LEN: usize = 150;
+const MAX_ACTIONS_ELEMENTS: usize = 25;
+const MAX_CONTEXT_ELEMENTS: usize = 10;
+const MAX_ALT_TEXT_LEN: usize = 2000;
+pub const MAX_TEXT_LEN: usize = 40_000;
+
+const BLOCK_TYPES: &[&str] = &[
+    "actions",
+    "context",
+    "divider",
+    "file",
+    "header",
+    "image",
+    "input",
+    "rich_text",
+    "section",
+    "video",
+];
+
+/// Accept either a TOML array of tables or a string holding the JSON copied
+/// from Block Kit Builder.
+pub fn normalize(value: Value) -> Result<Value, String> {
+    match value {
+        Value::String(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("is not valid JSON: {}", e))
+        }
+        other => Ok(other),
+    }
+}
+
+/// Check the structure of a `blocks` array against Slack's rules. Returns
+/// every problem found, each prefixed with its path.
+pub fn validate_blocks(blocks: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    let Some(blocks) = blocks.as_array() else {
+        return vec!["must be an array of blocks".to_string()];
+    };
+    if blocks.len() > MAX_BLOCKS {
+        errors.push(format!(
+            "has {} blocks, Slack allows at most {}",
+            blocks.len(),
+            MAX_BLOCKS
+        ));
+    }
+
+    let mut block_ids = HashSet::new();
+    for (index, block) in blocks.iter().enumerate() {
+        let path = format!("[{}]", index);
+        let Some(block) = block.as_object() else {
+            errors.push(format!("{}: must be a table/object", path));
+            continue;
+        };
+        if let Some(block_id) = block.get("block_id") {
+            match block_id.as_str() {
+                Some(id) if id.chars().count() > MAX_BLOCK_ID_LEN => errors.push(format!(
+                    "{}.block_id: longer than {} characters",
+                    path, MAX_BLOCK_ID_LEN
+                )),
+                Some(id) if !block_ids.insert(id.to_string()) => {
+                    errors.push(format!("{}.block_id: duplicate '{}'", path, id))
+                }
+                Some(_) => {}
+                None => errors.push(format!("{}.block_id: must be a string", path)),
+            }
+        }
+        match block.get("type").and_then(Value::as_str) {
+            Some(kind) if BLOCK_TYPES.contains(&kind) => {
+                validate_block(kind, block, &path, &mut errors)
+            }
+            Some(kind) => errors.push(format!("{}.type: unknown block type '{}'", path, kind)),
+            None => errors.push(format!("{}.type: missing", path)),
+        }
+    }
+    errors
+}
+
+fn validate_block(kind: &str, block: &Map<String, Value>, path: &str, errors: &mut Vec<String>) {
+    match kind {
+        "section" => {
+            let text = block.get("text");
+            let fields = block.get("fields");
+            if text.is_none() && fields.is_none() {
+                errors.push(format!("{}: section needs `text` or `fields`", path));
+            }
+            if let Some(text) = text {
+                validate_text(
+                    text,
+                    &format!("{}.text", path),
+                    MAX_SECTION_TEXT_LEN,
+                    errors,
+                );
+            }
+            if let Some(fields) = fields {
+                match fields.as_array() {
+                    Some(fields) => {
+                        if fields.len() > MAX_SECTION_FIELDS {
+                            errors.push(format!(
+                                "{}.fields: at most {} fields allowed",
+                                path, MAX_SECTION_FIELDS
+                            ));
+                        }
+                        for (i, field) in fields.iter().enumerate() {
+                            let field_path = format!("{}.fields[{}]", path, i);
+                            validate_text(field, &field_path, MAX_FIELD_TEXT_LEN, errors);
+                        }
+                    }
+                    None => errors.push(format!("{}.fields: must be an array", path)),
+                }
+            }
+        }
+        "header" => match block.get("text") {
+            Some(text) => {
+                let text_path = format!("{}.text", path);
+                validate_text(text, &text_path, MAX_HEADER_TEXT_LEN, errors);
+                if text.get("type").and_then(Value::as_str) != Some("plain_text") {
+                    errors.push(format!("{}: header text must be plain_text", text_path));
+                }
+            }
+            None => errors.push(format!("{}: header needs `text`", path)),
+        },
+        "actions" => validate_elements(block, path, MAX_ACTIONS_ELEMENTS, errors),
+        "context" => validate_elements(block, path, MAX_CONTEXT_ELEMENTS, errors),
+        "image" => {
+            if block.get("image_url").is_none() && block.get("slack_file").is_none() {
+                errors.push(format!("{}: image needs `image_url` or `slack_file`", path));
+            }
+            match block.get("alt_text").and_then(Value::as_str) {
+                Some(alt) if alt.chars().count() > MAX_ALT_TEXT_LEN => errors.push(format!(
+                    "{}.alt_text: longer than {} characters",
+                    path, MAX_ALT_TEXT_LEN
+                )),
+                Some(_) => {}
+                None => errors.push(format!("{}: image needs `alt_text`", path)),
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_elements(block: &Map<String, Value>, path: &str, max: usize, errors: &mut Vec<String>) {
+    match block.get("elements").and_then(Value::as_array) {
+        Some(elements) if elements.is_empty() => {
+            errors.push(format!("{}.elements: must not be empty", path))
+        }
+        Some(elements) if elements.len() > max => errors.push(format!(
+            "{}.elements: at most {} elements allowed",
+            path, max
+        )),
+        Some(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                if element.get("type").and_then(Value::as_str).is_none() {
+                    errors.push(format!("{}.elements[{}].type: missing", path, i));
+                }
+            }
+        }
+        None => errors.push(format!("{}.elements: must be an array", path)),
+    }
+}
+
+/// A composition text object: `{ type = "plain_text" | "mrkdwn", text = "..." }`.
+fn validate_text(text: &Value, path: &str, max_len: usize, errors: &mut Vec<String>) {
+    match text.get("type").and_then(Value::as_str) {
+        Some("plain_text") | Some("mrkdwn") => {}
+        Some(other) => errors.push(format!("{}.type: unknown text type '{}'", path, other)),
+        None => errors.push(format!("{}.type: missing", path)),
+    }
+    match text.get("text").and_then(Value::as_str) {
+        Some("") => errors.push(format!("{}.text: must not be empty", path)),
+        Some(body) if body.chars().count() > max_len => {
+            errors.push(format!("{}.text: longer than {} characters", path, max_len))
+        }
+        Some(_) => {}
+        None => errors.push(format!("{}.text: missing", path)),
+    }
+}
+
+/// Legacy attachments only need to be an array of objects.
+pub fn validate_attachments(attachments: &Value) -> Vec<String> {
+    let Some(attachments) = attachments.as_array() else {
+        return vec!["must be an array of attachments".to_string()];
+    };
+    let mut errors = Vec::new();
+    if attachments.len() > MAX_ATTACHMENTS {
+        errors.push(format!(
+            "has {} attachments, Slack allows at most {}",
+            attachments.len(),
+            MAX_ATTACHMENTS
+        ));
+    }
+    for (index, attachment) in attachments.iter().enumerate() {
+        if !attachment.is_object() {
+            errors.push(format!("[{}]: must be a table/object", index));
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn section(text: &str) -> Value {
+        json!({ "type": "section", "text": { "type": "mrkdwn", "text": text } })
+    }
+
+    #[test]
+    fn accepts_blocks_as_tables_or_json() {
+        let tables = json!([
+            { "type": "header", "text": { "type": "plain_text", "text": "Standup" } },
+            section("What did you do yesterday?"),
+            { "type": "divider" },
+            { "type": "context", "elements": [{ "type": "mrkdwn", "text": "Be brief" }] },
+        ]);
+        assert!(validate_blocks(&tables).is_empty());
+
+        let pasted = normalize(Value::String(tables.to_string())).unwrap();
+        assert_eq!(pasted, tables);
+        assert!(normalize(Value::String("[{".to_string()))
+            .unwrap_err()
+            .starts_with("is not valid JSON"));
+    }
+
+    #[test]
+    fn enforces_slack_limits() {
+        let many = Value::Array(vec![section("hi"); MAX_BLOCKS + 1]);
+        assert_eq!(
+            validate_blocks(&many),
+            ["has 51 blocks, Slack allows at most 50"]
+        );
+        assert!(validate_blocks(&Value::Array(vec![section("hi"); MAX_BLOCKS])).is_empty());
+
+        let long = "x".repeat(MAX_SECTION_TEXT_LEN + 1);
+        let header = "x".repeat(MAX_HEADER_TEXT_LEN + 1);
+        let blocks = json!([
+            section(&long),
+            section(""),
+            { "type": "header", "text": { "type": "mrkdwn", "text": header } },
+            { "type": "image", "image_url": "https://example.com/a.png" },
+            { "type": "actions", "elements": [] },
+        ]);
+        assert_eq!(
+            validate_blocks(&blocks),
+            [
+                "[0].text.text: longer than 3000 characters",
+                "[1].text.text: must not be empty",
+                "[2].text.text: longer than 150 characters",
+                "[2].text: header text must be plain_text",
+                "[3]: image needs `alt_text`",
+                "[4].elements: must not be empty",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_blocks() {
+        let blocks = json!([
+            { "type": "carousel" },
+            { "text": "no type" },
+            "not a table",
+            { "type": "section", "block_id": "a" },
+            { "type": "divider", "block_id": "a" },
+        ]);
+        assert_eq!(
+            validate_blocks(&blocks),
+            [
+                "[0].type: unknown block type 'carousel'",
+                "[1].type: missing",
+                "[2]: must be a table/object",
+                "[3]: section needs `text` or `fields`",
+                "[4].block_id: duplicate 'a'",
+            ]
+        );
+        assert_eq!(validate_blocks(&json!({})), ["must be an array of blocks"]);
+    }
+
+    #[test]
+    fn checks_attachments() {
+        assert!(validate_attachments(&json!([{ "color": "#36a64f", "text": "hi" }])).is_empty());
+        assert_eq!(
+            validate_attachments(&json!([{}, 1])),
+            ["[1]: must be a table/object"]
+        );
+        let many = Value::Array(vec![json!({}); MAX_ATTACHMENTS + 1]);
+        assert_eq!(
+            validate_attachments(&many),
+            ["has 101 attachments, Slack allows at most 100"]
+        );
+        assert_eq!(
+            validate_attachments(&json!("text")),
+            ["must be an array of attachments"]
+        );
+    }
+}
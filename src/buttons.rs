@@ -0,0 +1,412 @@
+use crate::scheduler::Context;
+use crate::slack::SlackMessage;
+use crate::state::Snooze;
+use chrono::{DateTime, Days, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// `block_id` of the buttons block, so it can be found and removed again.
+const BLOCK_ID: &str = "reminder_buttons";
+
+const ACTION_DONE: &str = "reminder_done";
+const ACTION_SNOOZE_HOUR: &str = "reminder_snooze_1h";
+const ACTION_SNOOZE_TOMORROW: &str = "reminder_snooze_tomorrow";
+
+/// Local hour "Snooze until tomorrow" re-posts at.
+const TOMORROW_AT_HOUR: u32 = 9;
+
+/// Carried in each button's `value`, so a press can be handled without
+/// looking the reminder up (it may have been removed since).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Target {
+    pub reminder: String,
+    pub workspace: String,
+    pub timezone: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Done,
+    SnoozeHour,
+    SnoozeTomorrow,
+}
+
+/// A click on one of the buttons, from a `block_actions` payload.
+#[derive(Debug)]
+pub struct Press {
+    pub action: Action,
+    pub target: Target,
+    pub user: String,
+    pub channel: String,
+    pub ts: String,
+    pub thread_ts: Option<String>,
+    pub text: String,
+    /// The message's blocks without the buttons.
+    pub blocks: Value,
+}
+
+#[derive(Deserialize)]
+struct BlockActions {
+    #[serde(rename = "type")]
+    kind: String,
+    user: Id,
+    channel: Id,
+    message: Message,
+    #[serde(default)]
+    actions: Vec<ActionPayload>,
+}
+
+#[derive(Deserialize)]
+struct Id {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    ts: String,
+    thread_ts: Option<String>,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    blocks: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct ActionPayload {
+    action_id: String,
+    #[serde(default)]
+    value: String,
+}
+
+/// `blocks` (or a section holding `text` if there are none) followed by the
+/// "Done" and "Snooze" buttons for `target`.
+pub fn attach(blocks: Option<&Value>, text: &str, target: &Target) -> Value {
+    let mut blocks = match blocks.and_then(Value::as_array) {
+        Some(blocks) => blocks.clone(),
+        None => vec![json!({ "type": "section", "text": { "type": "mrkdwn", "text": text } })],
+    };
+    let value = serde_json::to_string(target).expect("target serializes");
+    let button = |action_id: &str, label: &str| {
+        json!({
+            "type": "button",
+            "action_id": action_id,
+            "text": { "type": "plain_text", "text": label },
+            "value": value,
+        })
+    };
+    let mut done = button(ACTION_DONE, "Done");
+    done["style"] = json!("primary");
+    blocks.push(json!({
+        "type": "actions",
+        "block_id": BLOCK_ID,
+        "elements": [
+            done,
+            button(ACTION_SNOOZE_HOUR, "Snooze 1h"),
+            button(ACTION_SNOOZE_TOMORROW, "Snooze until tomorrow"),
+        ],
+    }));
+    Value::Array(blocks)
+}
+
+/// The press described by an interactive payload, if it is one of ours.
+pub fn parse(payload: &Value) -> Option<Press> {
+    let payload: BlockActions = serde_json::from_value(payload.clone()).ok()?;
+    if payload.kind != "block_actions" {
+        return None;
+    }
+    let pressed = payload.actions.first()?;
+    let action = match pressed.action_id.as_str() {
+        ACTION_DONE => Action::Done,
+        ACTION_SNOOZE_HOUR => Action::SnoozeHour,
+        ACTION_SNOOZE_TOMORROW => Action::SnoozeTomorrow,
+        _ => return None,
+    };
+    let target = serde_json::from_str(&pressed.value).ok()?;
+    let blocks = payload
+        .message
+        .blocks
+        .into_iter()
+        .filter(|block| block["block_id"] != BLOCK_ID)
+        .collect();
+    Some(Press {
+        action,
+        target,
+        user: payload.user.id,
+        channel: payload.channel.id,
+        ts: payload.message.ts,
+        thread_ts: payload.message.thread_ts,
+        text: payload.message.text,
+        blocks: Value::Array(blocks),
+    })
+}
+
+/// Mark the message done, or snooze it: record the re-post, show who
+/// snoozed it until when, and schedule it.
+pub async fn press(press: Press, context: Arc<Context>, shutdown: CancellationToken) {
+    let Some(client) = context.clients.get(&press.target.workspace) else {
+        eprintln!(
+            "Ignoring button press for '{}': no workspace '{}'",
+            press.target.reminder, press.target.workspace
+        );
+        return;
+    };
+    let now = Utc::now();
+    let note = match press.action {
+        Action::Done => {
+            println!(
+                "'{}' in {} marked done by {}",
+                press.target.reminder, press.channel, press.user
+            );
+            format!(":white_check_mark: Done by <@{}>", press.user)
+        }
+        Action::SnoozeHour | Action::SnoozeTomorrow => {
+            let due = snooze_until(press.action, &press.target.timezone, now);
+            let snooze = Snooze {
+                id: 0,
+                reminder: press.target.reminder.clone(),
+                workspace: press.target.workspace.clone(),
+                timezone: press.target.timezone.clone(),
+                channel: press.channel.clone(),
+                thread_ts: press.thread_ts.clone(),
+                text: press.text.clone(),
+                blocks: Some(press.blocks.clone()),
+                due,
+                snoozed_by: press.user.clone(),
+            };
+            match context.state.add_snooze(snooze.clone()).await {
+                Ok(id) => {
+                    println!(
+                        "'{}' in {} snoozed by {} until {}",
+                        press.target.reminder, press.channel, press.user, due
+                    );
+                    tokio::spawn(repost(
+                        Snooze { id, ..snooze },
+                        Arc::clone(&context),
+                        shutdown,
+                    ));
+                }
+                Err(e) => {
+                    eprintln!(
+                        "Failed to record snooze of '{}': {}",
+                        press.target.reminder, e
+                    );
+                    return;
+                }
+            }
+            format!(
+                ":zzz: Snoozed by <@{}> until <!date^{}^{{date_short_pretty}} at {{time}}|{}>",
+                press.user,
+                due.timestamp(),
+                due.to_rfc2822()
+            )
+        }
+    };
+
+    let mut blocks = press.blocks.as_array().cloned().unwrap_or_default();
+    blocks.push(json!({
+        "type": "context",
+        "elements": [{ "type": "mrkdwn", "text": note }],
+    }));
+    let blocks = Value::Array(blocks);
+    let label = format!("chat.update {}", press.target.reminder);
+    if let Err(e) = context
+        .retry
+        .run(&label, || {
+            client.update_message(&press.channel, &press.ts, &press.text, &blocks)
+        })
+        .await
+    {
+        eprintln!(
+            "Failed to update message of '{}' in {}: {}",
+            press.target.reminder, press.channel, e
+        );
+    }
+}
+
+/// Spawn the re-posts of snoozes recorded before a restart; overdue ones
+/// go out right away.
+pub fn resume(context: &Arc<Context>, shutdown: &CancellationToken) {
+    for snooze in context.state.snoozes() {
+        println!(
+            "Re-posting snoozed '{}' in {} at {}",
+            snooze.reminder, snooze.channel, snooze.due
+        );
+        tokio::spawn(repost(snooze, Arc::clone(context), shutdown.clone()));
+    }
+}
+
+/// Wait until a snooze is due, then post the message again with its
+/// buttons and forget the snooze.
+async fn repost(snooze: Snooze, context: Arc<Context>, shutdown: CancellationToken) {
+    let wait = (snooze.due - Utc::now()).to_std().unwrap_or_default();
+    tokio::select! {
+        _ = shutdown.cancelled() => return,
+        _ = tokio::time::sleep(wait) => {}
+    }
+    let Some(client) = context.clients.get(&snooze.workspace) else {
+        eprintln!(
+            "Dropping snooze of '{}': no workspace '{}'",
+            snooze.reminder, snooze.workspace
+        );
+        let _ = context.state.remove_snooze(snooze.id).await;
+        return;
+    };
+    let target = Target {
+        reminder: snooze.reminder.clone(),
+        workspace: snooze.workspace.clone(),
+        timezone: snooze.timezone.clone(),
+    };
+    let blocks = attach(snooze.blocks.as_ref(), &snooze.text, &target);
+    let message = SlackMessage {
+        channel: &snooze.channel,
+        text: &snooze.text,
+        blocks: Some(&blocks),
+        attachments: None,
+        thread_ts: snooze.thread_ts.as_deref(),
+        reply_broadcast: None,
+    };
+    let label = format!("{} snoozed to {}", snooze.reminder, snooze.channel);
+    match context
+        .retry
+        .run(&label, || client.post_message(&message))
+        .await
+    {
+        Ok(_) => println!(
+            "Re-posted snoozed '{}' in {}",
+            snooze.reminder, snooze.channel
+        ),
+        Err(e) => eprintln!(
+            "Failed to re-post snoozed '{}' in {}: {}",
+            snooze.reminder, snooze.channel, e
+        ),
+    }
+    if let Err(e) = context.state.remove_snooze(snooze.id).await {
+        eprintln!("Failed to forget snooze of '{}': {}", snooze.reminder, e);
+    }
+}
+
+/// An hour from now, or [`TOMORROW_AT_HOUR`] tomorrow in the reminder's zone.
+fn snooze_until(action: Action, timezone: &str, now: DateTime<Utc>) -> DateTime<Utc> {
+    let hour_later = now + chrono::Duration::hours(1);
+    if action != Action::SnoozeTomorrow {
+        return hour_later;
+    }
+    let tz = Tz::from_str(timezone).unwrap_or(Tz::UTC);
+    now.with_timezone(&tz)
+        .date_naive()
+        .checked_add_days(Days::new(1))
+        .and_then(|day| day.and_hms_opt(TOMORROW_AT_HOUR, 0, 0))
+        .and_then(|local| tz.from_local_datetime(&local).earliest())
+        .map(|local| local.with_timezone(&Utc))
+        .unwrap_or(now + chrono::Duration::days(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> Target {
+        Target {
+            reminder: "standup".to_string(),
+            workspace: "default".to_string(),
+            timezone: "Europe/Berlin".to_string(),
+        }
+    }
+
+    /// A `block_actions` payload for pressing the button `index` of `blocks`.
+    fn payload(blocks: &Value, index: usize) -> Value {
+        let buttons = &blocks.as_array().unwrap().last().unwrap()["elements"];
+        json!({
+            "type": "block_actions",
+            "user": { "id": "U0123456789" },
+            "channel": { "id": "C0123456789" },
+            "message": {
+                "ts": "1700000000.000100",
+                "thread_ts": "1699990000.000100",
+                "text": "Standup",
+                "blocks": blocks,
+            },
+            "actions": [buttons[index]],
+        })
+    }
+
+    #[test]
+    fn attaches_buttons_after_the_blocks() {
+        let blocks = attach(None, "Standup", &target());
+        let blocks = blocks.as_array().unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0]["text"]["text"], "Standup");
+        assert_eq!(blocks[1]["block_id"], BLOCK_ID);
+        assert_eq!(blocks[1]["elements"].as_array().unwrap().len(), 3);
+
+        let own = json!([{ "type": "divider" }]);
+        let blocks = attach(Some(&own), "Standup", &target());
+        assert_eq!(blocks[0], own[0]);
+        assert_eq!(blocks.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn parses_presses_of_our_buttons() {
+        let blocks = attach(None, "Standup", &target());
+        let actions = [Action::Done, Action::SnoozeHour, Action::SnoozeTomorrow];
+        for (index, action) in actions.into_iter().enumerate() {
+            let press = parse(&payload(&blocks, index)).unwrap();
+            assert_eq!(press.action, action);
+            assert_eq!(press.target.reminder, "standup");
+            assert_eq!(press.target.timezone, "Europe/Berlin");
+            assert_eq!(press.user, "U0123456789");
+            assert_eq!(press.channel, "C0123456789");
+            assert_eq!(press.ts, "1700000000.000100");
+            assert_eq!(press.thread_ts.as_deref(), Some("1699990000.000100"));
+            // The buttons are dropped so the updated message has none
+            assert_eq!(press.blocks, json!([blocks[0]]));
+        }
+    }
+
+    #[test]
+    fn ignores_other_payloads() {
+        let blocks = attach(None, "Standup", &target());
+        let mut other = payload(&blocks, 0);
+        other["actions"][0]["action_id"] = json!("something_else");
+        assert!(parse(&other).is_none());
+
+        let mut shortcut = payload(&blocks, 0);
+        shortcut["type"] = json!("shortcut");
+        assert!(parse(&shortcut).is_none());
+
+        let mut garbled = payload(&blocks, 0);
+        garbled["actions"][0]["value"] = json!("not json");
+        assert!(parse(&garbled).is_none());
+
+        let mut empty = payload(&blocks, 0);
+        empty["actions"] = json!([]);
+        assert!(parse(&empty).is_none());
+    }
+
+    #[test]
+    fn snoozes_an_hour_or_until_tomorrow_morning() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 28, 22, 30, 0).unwrap();
+        assert_eq!(
+            snooze_until(Action::SnoozeHour, "Europe/Berlin", now),
+            Utc.with_ymd_and_hms(2026, 3, 28, 23, 30, 0).unwrap()
+        );
+        // 23:30 in Berlin, so tomorrow is March 29, which is already summer time
+        assert_eq!(
+            snooze_until(Action::SnoozeTomorrow, "Europe/Berlin", now),
+            Utc.with_ymd_and_hms(2026, 3, 29, 7, 0, 0).unwrap()
+        );
+        // Still March 28 in New York
+        assert_eq!(
+            snooze_until(Action::SnoozeTomorrow, "America/New_York", now),
+            Utc.with_ymd_and_hms(2026, 3, 29, 13, 0, 0).unwrap()
+        );
+        assert_eq!(
+            snooze_until(Action::SnoozeTomorrow, "Not/A_Zone", now),
+            Utc.with_ymd_and_hms(2026, 3, 29, 9, 0, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use crate::config::{HolidayPolicy, Reminder};
+use crate::schedule::{self, Occurrence};
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, Utc, Weekday};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Longest single event or inline range, in days.
+const MAX_RANGE_DAYS: u64 = 366;
+
+/// Years a `FREQ=YEARLY` event is expanded for when it has no end.
+const YEARLY_HORIZON: i32 = 100;
+
+/// How far a move to another business day may reach.
+const MAX_SHIFT_DAYS: u64 = 366;
+
+/// Scheduled occurrences looked at before giving up on finding one to fire.
+const MAX_SCAN: usize = 100_000;
+
+/// The days of a `[[calendar]]`, each with what it is: an event summary, or
+/// the calendar's name for inline dates.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Calendar {
+    pub days: BTreeMap<NaiveDate, String>,
+}
+
+impl Calendar {
+    /// Add the events of an iCalendar file. Only all-day and timed
+    /// `VEVENT`s are read, plus yearly recurrences.
+    pub fn add_ics(&mut self, path: &Path, calendar: &str) -> Result<(), String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        for (date, summary) in parse_ics(&contents)? {
+            let label = match summary {
+                Some(summary) => format!("{} in calendar '{}'", summary, calendar),
+                None => format!("an event in calendar '{}'", calendar),
+            };
+            self.days.entry(date).or_insert(label);
+        }
+        Ok(())
+    }
+
+    /// Add an inline date, `2026-12-24`, or an inclusive range,
+    /// `2026-12-24..2027-01-01`.
+    pub fn add_dates(&mut self, text: &str, calendar: &str) -> Result<(), String> {
+        let parse = |date: &str| {
+            NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+                .map_err(|_| format!("'{}' is not a date like 2026-12-24", date.trim()))
+        };
+        let (first, last) = match text.split_once("..") {
+            Some((first, last)) => (parse(first)?, parse(last)?),
+            None => (parse(text)?, parse(text)?),
+        };
+        if last < first {
+            return Err(format!("'{}' ends before it starts", text));
+        }
+        for date in first.iter_days().take_while(|date| *date <= last) {
+            if (date - first).num_days() as u64 >= MAX_RANGE_DAYS {
+                return Err(format!("'{}' is longer than a year", text));
+            }
+            self.days
+                .entry(date)
+                .or_insert_with(|| format!("listed in calendar '{}'", calendar));
+        }
+        Ok(())
+    }
+}
+
+/// The calendar days a reminder does not fire on, and what happens to
+/// occurrences that fall on them. Business days are Monday to Friday
+/// outside the calendars.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Blackout {
+    pub days: BTreeMap<NaiveDate, String>,
+    pub policy: HolidayPolicy,
+}
+
+impl Blackout {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.days.contains_key(&date)
+    }
+
+    /// The nearest business day after (`forward`) or before `date`.
+    fn business_day(&self, date: NaiveDate, forward: bool) -> Option<NaiveDate> {
+        let mut date = date;
+        for _ in 0..MAX_SHIFT_DAYS {
+            date = if forward {
+                date.succ_opt()?
+            } else {
+                date.pred_opt()?
+            };
+            if self.is_business_day(date) {
+                return Some(date);
+            }
+        }
+        None
+    }
+}
+
+/// The next time a reminder fires, after applying its calendars.
+#[derive(Debug, Clone)]
+pub struct Planned {
+    pub occurrence: Occurrence,
+    /// The scheduled occurrence this was moved from, and why.
+    pub moved_from: Option<(Occurrence, String)>,
+    /// Scheduled occurrences before this one that are skipped, and why.
+    pub skipped: Vec<(Occurrence, String)>,
+}
+
+/// Next time `reminder` fires strictly after `after`: its schedule's next
+/// occurrence, unless calendars skip or move it, within its `starts_at` and
+/// `ends_at`.
+pub fn next_fire(reminder: &Reminder, after: DateTime<Utc>) -> Option<Planned> {
+    let after = match reminder.starts_at {
+        Some(starts_at) => after.max(starts_at - chrono::Duration::seconds(1)),
+        None => after,
+    };
+    let mut planned = next_in_calendars(reminder, after)?;
+    if let Some(starts_at) = reminder.starts_at {
+        planned
+            .skipped
+            .retain(|(skipped, _)| skipped.utc >= starts_at);
+    }
+    match reminder.ends_at {
+        Some(ends_at) if planned.occurrence.utc > ends_at => None,
+        _ => Some(planned),
+    }
+}
+
+fn next_in_calendars(reminder: &Reminder, after: DateTime<Utc>) -> Option<Planned> {
+    let tz = reminder.timezone;
+    let Some(blackout) = &reminder.blackout else {
+        return schedule::next_after(&reminder.schedule, tz, after).map(|occurrence| Planned {
+            occurrence,
+            moved_from: None,
+            skipped: Vec::new(),
+        });
+    };
+
+    // An occurrence already past can still be moved forward to after
+    // `after`, from as far back as the non-business days before it reach.
+    let mut from = after.with_timezone(&tz).date_naive();
+    if blackout.policy == HolidayPolicy::NextBusinessDay {
+        for _ in 0..MAX_SHIFT_DAYS {
+            match from.pred_opt() {
+                Some(day) if !blackout.is_business_day(day) => from = day,
+                _ => break,
+            }
+        }
+    }
+    let mut cursor = (day_start(from, reminder) - chrono::Duration::seconds(1)).min(after);
+
+    let mut best: Option<Planned> = None;
+    let mut skipped = Vec::new();
+    for _ in 0..MAX_SCAN {
+        let Some(scheduled) = schedule::next_after(&reminder.schedule, tz, cursor) else {
+            break;
+        };
+        cursor = scheduled.utc;
+        let date = scheduled.local.date_naive();
+        // Nothing scheduled past the business day after the best candidate
+        // can be moved before it.
+        if let Some(best) = &best {
+            let best_date = best.occurrence.local.date_naive();
+            match blackout.business_day(best_date, true) {
+                Some(limit) if date <= limit => {}
+                _ => break,
+            }
+        }
+        let earlier = |candidate: &Occurrence, best: &Option<Planned>| {
+            candidate.utc > after
+                && best
+                    .as_ref()
+                    .is_none_or(|best| candidate.utc < best.occurrence.utc)
+        };
+
+        let Some(reason) = blackout.days.get(&date) else {
+            if earlier(&scheduled, &best) {
+                best = Some(Planned {
+                    occurrence: scheduled,
+                    moved_from: None,
+                    skipped: Vec::new(),
+                });
+            }
+            continue;
+        };
+        let reason = format!("{} is {}", date, reason);
+        let target = match blackout.policy {
+            HolidayPolicy::Skip => {
+                if scheduled.utc > after {
+                    skipped.push((scheduled, reason));
+                }
+                // The rest of the day is skipped as well
+                cursor = day_start(date.succ_opt()?, reminder) - chrono::Duration::seconds(1);
+                continue;
+            }
+            HolidayPolicy::NextBusinessDay => blackout.business_day(date, true),
+            HolidayPolicy::PreviousBusinessDay => blackout.business_day(date, false),
+        };
+        let Some(target) = target else {
+            continue;
+        };
+        let moved = at_local(target, scheduled.local.time(), reminder);
+        if earlier(&moved, &best) {
+            best = Some(Planned {
+                occurrence: moved,
+                moved_from: Some((scheduled, reason)),
+                skipped: Vec::new(),
+            });
+        }
+    }
+
+    let mut best = best?;
+    skipped.retain(|(occurrence, _)| occurrence.utc < best.occurrence.utc);
+    best.skipped = skipped;
+    Some(best)
+}
+
+/// The start of `date` in the reminder's zone.
+fn day_start(date: NaiveDate, reminder: &Reminder) -> DateTime<Utc> {
+    at_local(date, NaiveTime::MIN, reminder).utc
+}
+
+fn at_local(date: NaiveDate, time: NaiveTime, reminder: &Reminder) -> Occurrence {
+    let utc = schedule::resolve_local(reminder.timezone, date.and_time(time));
+    Occurrence::from_utc(utc, reminder.timezone)
+}
+
+/// The dates covered by each `VEVENT`, with its `SUMMARY`.
+fn parse_ics(contents: &str) -> Result<Vec<(NaiveDate, Option<String>)>, String> {
+    // Lines starting with a space or tab continue the previous one
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut days = Vec::new();
+    let mut event: Option<Event> = None;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.split(';').next().unwrap_or_default();
+        match (name.to_ascii_uppercase().as_str(), event.as_mut()) {
+            ("BEGIN", None) if value == "VEVENT" => event = Some(Event::default()),
+            ("END", Some(_)) if value == "VEVENT" => {
+                let done = event.take().expect("inside an event");
+                days.extend(done.days()?);
+            }
+            ("DTSTART", Some(event)) => event.start = Some(ics_date(value)?.0),
+            ("DTEND", Some(event)) => {
+                // An all-day end (or one at midnight) is exclusive
+                let (date, timed) = ics_date(value)?;
+                event.end = Some(if timed {
+                    date
+                } else {
+                    date.pred_opt().unwrap_or(date)
+                });
+            }
+            ("SUMMARY", Some(event)) => event.summary = Some(unescape(value)),
+            ("RRULE", Some(event)) => event.rrule = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    Ok(days)
+}
+
+#[derive(Default)]
+struct Event {
+    start: Option<NaiveDate>,
+    /// Last day covered, inclusive.
+    end: Option<NaiveDate>,
+    summary: Option<String>,
+    rrule: Option<String>,
+}
+
+impl Event {
+    fn days(self) -> Result<Vec<(NaiveDate, Option<String>)>, String> {
+        let name = self.summary.as_deref().unwrap_or("an event");
+        let start = self
+            .start
+            .ok_or_else(|| format!("{} has no DTSTART", name))?;
+        let end = self.end.unwrap_or(start).max(start);
+        let length = (end - start).num_days() as u64;
+        if length >= MAX_RANGE_DAYS {
+            return Err(format!("{} is longer than a year", name));
+        }
+        let years = match &self.rrule {
+            None => 1,
+            Some(rule) => yearly_count(rule, start).ok_or_else(|| {
+                format!(
+                    "{} repeats with '{}'; only FREQ=YEARLY is supported",
+                    name, rule
+                )
+            })?,
+        };
+        let mut days = Vec::new();
+        for year in 0..years {
+            // Skipped in years without the date (29 February)
+            let Some(first) = start.with_year(start.year() + year) else {
+                continue;
+            };
+            for offset in 0..=length {
+                if let Some(date) = first.checked_add_days(Days::new(offset)) {
+                    days.push((date, self.summary.clone()));
+                }
+            }
+        }
+        Ok(days)
+    }
+}
+
+/// How many years a `FREQ=YEARLY` rule with optional `COUNT` or `UNTIL`
+/// covers; `None` for any other rule.
+fn yearly_count(rule: &str, start: NaiveDate) -> Option<i32> {
+    let mut yearly = false;
+    let mut count = YEARLY_HORIZON;
+    for part in rule.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => yearly = value.eq_ignore_ascii_case("YEARLY"),
+            "COUNT" => count = value.parse().ok()?,
+            "UNTIL" => count = ics_date(value).ok()?.0.year() - start.year() + 1,
+            "INTERVAL" if value == "1" => {}
+            "WKST" => {}
+            _ => return None,
+        }
+    }
+    yearly.then_some(count.clamp(0, YEARLY_HORIZON))
+}
+
+/// The date of a `DATE` or `DATE-TIME` value, and whether it has a time of
+/// day other than midnight.
+fn ics_date(value: &str) -> Result<(NaiveDate, bool), String> {
+    let value = value.trim();
+    let date = value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .ok_or_else(|| format!("'{}' is not an iCalendar date", value))?;
+    let timed = value.get(9..15).is_some_and(|time| time != "000000");
+    Ok((date, timed))
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CatchUp;
+    use crate::schedule::Schedule;
+    use chrono::{NaiveDateTime, TimeZone};
+    use std::str::FromStr;
+
+    const ICS: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20261225\r
+DTEND;VALUE=DATE:20261227\r
+SUMMARY:Christmas\\, both days\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20270101\r
+RRULE:FREQ=YEARLY;COUNT=2\r
+SUMMARY:New Year's\r
+  Day\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn reminder(schedule: &str, policy: HolidayPolicy, days: &[&str]) -> Reminder {
+        let mut calendar = Calendar::default();
+        for day in days {
+            calendar.add_dates(day, "test").unwrap();
+        }
+        Reminder {
+            name: "r".to_string(),
+            workspace: "default".to_string(),
+            schedule: Schedule::from_str(schedule).unwrap(),
+            destinations: Vec::new(),
+            text: String::new(),
+            timezone: chrono_tz::Europe::Berlin,
+            catch_up: CatchUp::Skip,
+            catch_up_grace: chrono::Duration::hours(1),
+            blocks: None,
+            attachments: None,
+            vars: BTreeMap::new(),
+            thread: None,
+            buttons: false,
+            ack: None,
+            blackout: Some(Blackout {
+                days: calendar.days,
+                policy,
+            }),
+            starts_at: None,
+            ends_at: None,
+            max_occurrences: None,
+            jitter: None,
+        }
+    }
+
+    fn next(reminder: &Reminder, after: &str) -> (String, Option<String>, usize) {
+        let after = NaiveDateTime::parse_from_str(after, "%Y-%m-%d %H:%M").unwrap();
+        let after = reminder.timezone.from_local_datetime(&after).unwrap();
+        let planned = next_fire(reminder, after.with_timezone(&Utc)).unwrap();
+        let format =
+            |occurrence: &Occurrence| occurrence.local.format("%a %Y-%m-%d %H:%M").to_string();
+        (
+            format(&planned.occurrence),
+            planned.moved_from.map(|(from, _)| format(&from)),
+            planned.skipped.len(),
+        )
+    }
+
+    #[test]
+    fn reads_all_day_and_yearly_events() {
+        let days = parse_ics(ICS).unwrap();
+        let dates: Vec<String> = days.iter().map(|(date, _)| date.to_string()).collect();
+        assert_eq!(
+            dates,
+            ["2026-12-25", "2026-12-26", "2027-01-01", "2028-01-01"]
+        );
+        assert_eq!(days[0].1.as_deref(), Some("Christmas, both days"));
+        assert_eq!(days[2].1.as_deref(), Some("New Year's Day"));
+        assert!(
+            parse_ics("BEGIN:VEVENT\nDTSTART:20260101\nRRULE:FREQ=WEEKLY\nEND:VEVENT").is_err()
+        );
+    }
+
+    #[test]
+    fn skips_blacked_out_days() {
+        let sundays = reminder("0 0 14 * * SUN", HolidayPolicy::Skip, &["2026-12-27"]);
+        assert_eq!(
+            next(&sundays, "2026-12-21 00:00"),
+            ("Sun 2027-01-03 14:00".to_string(), None, 1)
+        );
+    }
+
+    #[test]
+    fn moves_to_the_next_business_day() {
+        let sundays = reminder(
+            "0 0 14 * * SUN",
+            HolidayPolicy::NextBusinessDay,
+            &["2026-12-27", "2026-12-28"],
+        );
+        let moved = Some("Sun 2026-12-27 14:00".to_string());
+        assert_eq!(
+            next(&sundays, "2026-12-21 00:00"),
+            ("Tue 2026-12-29 14:00".to_string(), moved.clone(), 0)
+        );
+        // Still found after the scheduled time has passed
+        assert_eq!(
+            next(&sundays, "2026-12-28 09:00"),
+            ("Tue 2026-12-29 14:00".to_string(), moved, 0)
+        );
+        assert_eq!(next(&sundays, "2026-12-29 14:00").0, "Sun 2027-01-03 14:00");
+    }
+
+    #[test]
+    fn moves_to_the_previous_business_day_ahead_of_other_occurrences() {
+        // Fires Saturdays and Mondays; a Monday holiday moves to Friday,
+        // before the Saturday occurrence.
+        let reminder = reminder(
+            "every Saturday and Monday at 9",
+            HolidayPolicy::PreviousBusinessDay,
+            &["2026-12-28"],
+        );
+        let (first, moved_from, _) = next(&reminder, "2026-12-22 10:00");
+        assert_eq!(first, "Fri 2026-12-25 09:00");
+        assert_eq!(moved_from.as_deref(), Some("Mon 2026-12-28 09:00"));
+        assert_eq!(
+            next(&reminder, "2026-12-25 09:00").0,
+            "Sat 2026-12-26 09:00"
+        );
+        assert_eq!(
+            next(&reminder, "2026-12-26 09:00").0,
+            "Sat 2027-01-02 09:00"
+        );
+    }
+
+    #[test]
+    fn stays_within_starts_at_and_ends_at() {
+        let mut sundays = reminder("0 0 14 * * SUN", HolidayPolicy::Skip, &["2027-01-03"]);
+        let berlin = |text: &str| {
+            let local = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+            let local = sundays.timezone.from_local_datetime(&local).unwrap();
+            local.with_timezone(&Utc)
+        };
+        sundays.starts_at = Some(berlin("2027-01-10 14:00"));
+        sundays.ends_at = Some(berlin("2027-01-20 00:00"));
+        // Starts at starts_at, without reporting skips from before it
+        assert_eq!(
+            next(&sundays, "2026-12-21 00:00"),
+            ("Sun 2027-01-10 14:00".to_string(), None, 0)
+        );
+        assert_eq!(next(&sundays, "2027-01-10 14:00").0, "Sun 2027-01-17 14:00");
+        assert!(next_fire(&sundays, berlin("2027-01-17 14:00")).is_none());
+    }
+}
@@ -0,0 +1,631 @@
+use crate::blocks;
+use crate::calendar;
+use crate::config::{CatchUp, CommandSettings, ConfigError, Destination, Reminder};
+use crate::names::{self, NameCache};
+use crate::schedule::{self, Schedule};
+use crate::scheduler::{self, Context, ReloadSummary, Scheduler};
+use crate::state::StoredReminder;
+use crate::template;
+use chrono::Utc;
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+pub const USAGE: &str = "Usage:
+• `/remind-bot add \"<schedule>\" [#channel|@user] <text>` – the schedule is a phrase such as `every weekday at 9:30`, `first Monday of the month at 10am` or `every 2 weeks on Friday at 4pm`, a cron expression, `at 2026-11-03 15:00`, `every 90m [from 2026-11-03 09:00]` or `[DTSTART:20260104T090000] RRULE:FREQ=…`, e.g. `/remind-bot add \"every Monday at 9am\" #standup Standup time!`
+• `/remind-bot list`
+• `/remind-bot pause <name>` / `/remind-bot resume <name>`
+• `/remind-bot delete <name>`
+• `/remind-bot next [name]`";
+
+/// Upcoming occurrences shown by `next <name>` and after `add`.
+const NEXT_COUNT: usize = 5;
+
+/// Slack gives up on a slash command after 3 seconds.
+const REPLY_TIMEOUT: Duration = Duration::from_millis(2500);
+
+/// A parsed `/remind-bot` subcommand.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Without a destination the reminder posts to the channel the command
+    /// was typed in.
+    Add {
+        schedule: String,
+        destination: Option<Destination>,
+        text: String,
+    },
+    List,
+    Pause(String),
+    Resume(String),
+    Delete(String),
+    Next(Option<String>),
+    Help,
+}
+
+/// A command together with who sent it and from where.
+#[derive(Debug)]
+pub struct Invocation {
+    pub command: Command,
+    pub user_id: String,
+    pub channel_id: String,
+}
+
+/// A command waiting to be run; replies are sent back through `reply`.
+pub struct CommandRequest {
+    pub invocation: Invocation,
+    pub reply: oneshot::Sender<String>,
+}
+
+/// The fields of a slash command payload that we use; the same over HTTP
+/// (form-encoded) and Socket Mode (JSON).
+#[derive(Debug, Deserialize)]
+pub struct SlashCommand {
+    #[serde(default)]
+    pub text: String,
+    pub user_id: String,
+    pub channel_id: String,
+}
+
+/// Parse a verified slash command, hand it to the main loop and wait for the
+/// reply, within Slack's deadline.
+pub async fn dispatch(command: SlashCommand, requests: &mpsc::Sender<CommandRequest>) -> String {
+    let parsed = match parse(&command.text) {
+        Ok(parsed) => parsed,
+        Err(usage) => return usage,
+    };
+    let (reply, response) = oneshot::channel();
+    let request = CommandRequest {
+        invocation: Invocation {
+            command: parsed,
+            user_id: command.user_id,
+            channel_id: command.channel_id,
+        },
+        reply,
+    };
+    if requests.send(request).await.is_err() {
+        return "The bot is shutting down, try again shortly.".to_string();
+    }
+    match tokio::time::timeout(REPLY_TIMEOUT, response).await {
+        Ok(Ok(text)) => text,
+        Ok(Err(_)) => "The bot is shutting down, try again shortly.".to_string(),
+        Err(_) => "Still working on it; check `/remind-bot list` in a moment.".to_string(),
+    }
+}
+
+/// Parse the text after `/remind-bot`. The error is a reply for the user.
+pub fn parse(text: &str) -> Result<Command, String> {
+    let text = text.trim();
+    let (verb, rest) = match text.split_once(char::is_whitespace) {
+        Some((verb, rest)) => (verb, rest.trim()),
+        None => (text, ""),
+    };
+    let name = || {
+        if rest.is_empty() || rest.contains(char::is_whitespace) {
+            Err(format!("`{}` takes one reminder name.\n{}", verb, USAGE))
+        } else {
+            Ok(rest.to_string())
+        }
+    };
+    match verb.to_lowercase().as_str() {
+        "add" => parse_add(rest),
+        "list" => Ok(Command::List),
+        "pause" => name().map(Command::Pause),
+        "resume" => name().map(Command::Resume),
+        "delete" => name().map(Command::Delete),
+        "next" if rest.is_empty() => Ok(Command::Next(None)),
+        "next" => name().map(|name| Command::Next(Some(name))),
+        "" | "help" => Ok(Command::Help),
+        other => Err(format!("Unknown command `{}`.\n{}", other, USAGE)),
+    }
+}
+
+fn parse_add(rest: &str) -> Result<Command, String> {
+    // Slack clients may turn straight quotes into smart quotes
+    let rest = rest.replace(['“', '”'], "\"");
+    let Some((schedule, after)) = rest
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.split_once('"'))
+    else {
+        return Err(format!("Put the schedule in quotes.\n{}", USAGE));
+    };
+    let after = after.trim();
+    let (first, remainder) = match after.split_once(char::is_whitespace) {
+        Some((first, remainder)) => (first, remainder.trim()),
+        None => (after, ""),
+    };
+    let (destination, text) = match parse_destination(first) {
+        Some(destination) => (Some(destination), remainder),
+        None => (None, after),
+    };
+    if text.is_empty() {
+        return Err(format!("The reminder text is missing.\n{}", USAGE));
+    }
+    Ok(Command::Add {
+        schedule: schedule.trim().to_string(),
+        destination,
+        text: text.to_string(),
+    })
+}
+
+/// A channel or user as typed in Slack: escaped (`<#C123|name>`, `<@U123>`,
+/// `<!subteam^S123>`) or as a `#channel-name` / `@email` reference.
+fn parse_destination(token: &str) -> Option<Destination> {
+    if let Some(inner) = token.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+        let id = inner.split('|').next().unwrap_or_default();
+        if let Some(id) = id.strip_prefix('#') {
+            return Some(Destination::Channel(id.to_string()));
+        }
+        if let Some(id) = id.strip_prefix('@') {
+            return Some(Destination::User(id.to_string()));
+        }
+        if let Some(id) = id.strip_prefix("!subteam^") {
+            return Some(Destination::UserGroup(id.to_string()));
+        }
+        return None;
+    }
+    if token.len() > 1 && token.starts_with('#') {
+        return Some(Destination::Channel(token.to_string()));
+    }
+    if token.starts_with('@') && token[1..].contains('@') {
+        return Some(Destination::User(token.to_string()));
+    }
+    None
+}
+
+/// Build a runnable reminder from its stored form, checking it like the
+/// config file would.
+pub fn to_reminder(stored: &StoredReminder) -> Result<Reminder, String> {
+    let schedule = Schedule::from_str(&stored.schedule)?;
+    let timezone = Tz::from_str(&stored.timezone)
+        .map_err(|_| format!("unknown IANA time zone '{}'", stored.timezone))?;
+    template::validate(&stored.text, &BTreeMap::new())?;
+    if stored.text.chars().count() > blocks::MAX_TEXT_LEN {
+        return Err(format!(
+            "text is longer than {} characters",
+            blocks::MAX_TEXT_LEN
+        ));
+    }
+    let destinations: Vec<Destination> = stored
+        .channels
+        .iter()
+        .map(|id| Destination::Channel(id.clone()))
+        .chain(stored.users.iter().map(|id| Destination::User(id.clone())))
+        .chain(
+            stored
+                .usergroups
+                .iter()
+                .map(|id| Destination::UserGroup(id.clone())),
+        )
+        .collect();
+    for destination in &destinations {
+        names::check_reference(destination)?;
+    }
+    Ok(Reminder {
+        name: stored.name.clone(),
+        workspace: stored.workspace.clone(),
+        schedule,
+        destinations,
+        text: stored.text.clone(),
+        timezone,
+        catch_up: CatchUp::Skip,
+        catch_up_grace: chrono::Duration::hours(24),
+        blocks: None,
+        attachments: None,
+        vars: BTreeMap::new(),
+        thread: None,
+        buttons: false,
+        ack: None,
+        blackout: None,
+        starts_at: None,
+        ends_at: None,
+        max_occurrences: None,
+        jitter: None,
+    })
+}
+
+/// Every reminder that should be running: the configured ones plus those
+/// created from Slack, minus paused and ended ones.
+pub fn active(configured: &[Reminder], context: &Context) -> Vec<Reminder> {
+    let paused = context.state.paused();
+    let mut reminders: Vec<Reminder> = configured.to_vec();
+    for stored in context.state.stored_reminders() {
+        if configured.iter().any(|r| r.name == stored.name) {
+            eprintln!(
+                "Reminder '{}' from /remind-bot is shadowed by the config file",
+                stored.name
+            );
+            continue;
+        }
+        if !context.clients.contains_key(&stored.workspace) {
+            eprintln!(
+                "Skipping stored reminder '{}': workspace '{}' is not configured",
+                stored.name, stored.workspace
+            );
+            continue;
+        }
+        match to_reminder(&stored) {
+            Ok(reminder) => reminders.push(reminder),
+            Err(e) => eprintln!("Skipping stored reminder '{}': {}", stored.name, e),
+        }
+    }
+    reminders.retain(|reminder| !paused.contains(&reminder.name));
+    reminders.retain(|reminder| scheduler::ended(reminder, &context.state).is_none());
+    reminders
+}
+
+/// Run one command and return the reply. Commands that change which
+/// reminders should run notify `changes`, so the main loop, which owns the
+/// scheduler, can apply them.
+pub async fn execute(
+    invocation: Invocation,
+    context: &Context,
+    configured: &[Reminder],
+    settings: &CommandSettings,
+    names: &Mutex<NameCache>,
+    changes: &mpsc::Sender<()>,
+) -> String {
+    println!(
+        "/remind-bot from {} in {}: {:?}",
+        invocation.user_id, invocation.channel_id, invocation.command
+    );
+    match invocation.command {
+        Command::Add {
+            schedule,
+            destination,
+            text,
+        } => {
+            let destination =
+                destination.unwrap_or_else(|| Destination::Channel(invocation.channel_id.clone()));
+            let meaning = match schedule::interpret(&schedule) {
+                Ok((_, meaning)) => format!("`{}`, understood as {}", schedule.trim(), meaning),
+                Err(e) => return format!("Could not add the reminder: {}", e),
+            };
+            let mut stored = StoredReminder {
+                name: "new reminder".to_string(),
+                workspace: settings.workspace.clone(),
+                schedule,
+                timezone: settings.timezone.name().to_string(),
+                channels: Vec::new(),
+                users: Vec::new(),
+                usergroups: Vec::new(),
+                text,
+                created_by: invocation.user_id,
+                created_at: Utc::now(),
+            };
+            set_destinations(&mut stored, std::slice::from_ref(&destination));
+            let mut reminder = match to_reminder(&stored) {
+                Ok(reminder) => reminder,
+                Err(e) => return format!("Could not add the reminder: {}", e),
+            };
+            // Before anything is saved, so a schedule that cannot be
+            // evaluated is turned away rather than stored
+            let next = upcoming(&reminder, NEXT_COUNT);
+            if next.is_empty() {
+                return "Could not add the reminder: the schedule never fires.".to_string();
+            }
+            if let Err(e) = names::resolve(
+                std::slice::from_mut(&mut reminder),
+                &context.clients,
+                &context.retry,
+                &mut *names.lock().await,
+            )
+            .await
+            {
+                return format!("Could not add the reminder: {}", problems(e));
+            }
+            set_destinations(&mut stored, &reminder.destinations);
+            let name = match context.state.add_stored(stored).await {
+                Ok(name) => name,
+                Err(e) => return format!("Could not save the reminder: {}", e),
+            };
+            reminder.name = name.clone();
+            let _ = changes.send(()).await;
+            let mut lines = vec![format!(
+                "Added `{}` to {}: {} ({}). Next fires:",
+                name,
+                mentions(&reminder.destinations),
+                meaning,
+                reminder.timezone,
+            )];
+            lines.extend(next);
+            lines.join("\n")
+        }
+        Command::List => {
+            let state = &context.state;
+            let paused = state.paused();
+            let stored = state.stored_reminders();
+            let mut lines = Vec::new();
+            let status = |reminder: &Reminder| match scheduler::ended(reminder, state) {
+                Some(reason) => format!(" – *ended*: {}", reason),
+                None if paused.contains(&reminder.name) => " – *paused*".to_string(),
+                None => String::new(),
+            };
+            for reminder in configured {
+                lines.push(describe(reminder, "config file", &status(reminder)));
+            }
+            for entry in &stored {
+                if let Ok(reminder) = to_reminder(entry) {
+                    let origin = format!("added by <@{}>", entry.created_by);
+                    lines.push(describe(&reminder, &origin, &status(&reminder)));
+                }
+            }
+            if lines.is_empty() {
+                "No reminders yet.".to_string()
+            } else {
+                lines.join("\n")
+            }
+        }
+        Command::Pause(name) | Command::Resume(name) if !exists(&name, context, configured) => {
+            format!("No reminder named `{}`.", name)
+        }
+        Command::Pause(name) => match context.state.set_paused(&name, true).await {
+            Ok(false) => format!("`{}` is already paused.", name),
+            Ok(true) => {
+                let _ = changes.send(()).await;
+                format!("Paused `{}`.", name)
+            }
+            Err(e) => format!("Could not pause `{}`: {}", name, e),
+        },
+        Command::Resume(name) => match context.state.set_paused(&name, false).await {
+            Ok(false) => format!("`{}` is not paused.", name),
+            Ok(true) => {
+                let _ = changes.send(()).await;
+                format!("Resumed `{}`.", name)
+            }
+            Err(e) => format!("Could not resume `{}`: {}", name, e),
+        },
+        Command::Delete(name) if configured.iter().any(|r| r.name == name) => {
+            format!("`{}` is defined in the config file; remove it there.", name)
+        }
+        Command::Delete(name) => match context.state.remove_stored(&name).await {
+            Ok(false) => format!("No reminder named `{}`.", name),
+            Ok(true) => {
+                let _ = changes.send(()).await;
+                format!("Deleted `{}`.", name)
+            }
+            Err(e) => format!("Could not delete `{}`: {}", name, e),
+        },
+        Command::Next(Some(name)) => {
+            let state = &context.state;
+            let reminder = configured
+                .iter()
+                .find(|r| r.name == name)
+                .cloned()
+                .or_else(|| {
+                    state
+                        .stored_reminders()
+                        .iter()
+                        .find(|stored| stored.name == name)
+                        .and_then(|stored| to_reminder(stored).ok())
+                });
+            let Some(reminder) = reminder else {
+                return format!("No reminder named `{}`.", name);
+            };
+            if let Some(reason) = scheduler::ended(&reminder, state) {
+                return format!("`{}` has ended: {}.", name, reason);
+            }
+            let left = reminder.max_occurrences.map_or(u64::MAX, |max| {
+                max.saturating_sub(state.fire_count(&reminder.name))
+            });
+            let mut lines = vec![format!("Next fires of `{}`:", name)];
+            lines.extend(upcoming(&reminder, NEXT_COUNT.min(left as usize)));
+            if state.paused().contains(&name) {
+                lines.push("(paused, these will not be posted until resumed)".to_string());
+            }
+            lines.join("\n")
+        }
+        Command::Next(None) => {
+            let reminders = active(configured, context);
+            if reminders.is_empty() {
+                return "No active reminders.".to_string();
+            }
+            reminders
+                .iter()
+                .map(|reminder| format!("• `{}` {}", reminder.name, next_fire(reminder)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        Command::Help => USAGE.to_string(),
+    }
+}
+
+/// Bring the scheduler in line with the config file and the reminders added
+/// from Slack.
+pub fn apply(scheduler: &mut Scheduler, configured: &[Reminder]) -> ReloadSummary {
+    let reminders = active(configured, scheduler.context());
+    scheduler.reload(reminders)
+}
+
+fn exists(name: &str, context: &Context, configured: &[Reminder]) -> bool {
+    configured.iter().any(|r| r.name == name)
+        || context
+            .state
+            .stored_reminders()
+            .iter()
+            .any(|stored| stored.name == name)
+}
+
+fn set_destinations(stored: &mut StoredReminder, destinations: &[Destination]) {
+    stored.channels.clear();
+    stored.users.clear();
+    stored.usergroups.clear();
+    for destination in destinations {
+        match destination {
+            Destination::Channel(id) => stored.channels.push(id.clone()),
+            Destination::User(id) => stored.users.push(id.clone()),
+            Destination::UserGroup(id) => stored.usergroups.push(id.clone()),
+        }
+    }
+}
+
+fn describe(reminder: &Reminder, origin: &str, status: &str) -> String {
+    format!(
+        "• `{}` – `{}` ({}) to {}, {}{}",
+        reminder.name,
+        reminder.schedule,
+        reminder.timezone,
+        mentions(&reminder.destinations),
+        origin,
+        status
+    )
+}
+
+/// The next `count` fire times, one bullet each, noting those moved by a
+/// calendar.
+fn upcoming(reminder: &Reminder, count: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut after = Utc::now();
+    while lines.len() < count {
+        let Some(planned) = calendar::next_fire(reminder, after) else {
+            break;
+        };
+        let mut line = format!(
+            "• {}",
+            planned.occurrence.local.format("%a %Y-%m-%d %H:%M %Z")
+        );
+        if let Some((from, reason)) = &planned.moved_from {
+            line.push_str(&format!(
+                " (moved from {}: {})",
+                from.local.format("%a %Y-%m-%d"),
+                reason
+            ));
+        }
+        lines.push(line);
+        after = planned.occurrence.utc;
+    }
+    lines
+}
+
+fn next_fire(reminder: &Reminder) -> String {
+    match calendar::next_fire(reminder, Utc::now()) {
+        Some(planned) => format!(
+            "at {}",
+            planned.occurrence.local.format("%a %Y-%m-%d %H:%M %Z")
+        ),
+        None => "never".to_string(),
+    }
+}
+
+fn mentions(destinations: &[Destination]) -> String {
+    destinations
+        .iter()
+        .map(|destination| match destination {
+            Destination::Channel(id) => format!("<#{}>", id),
+            Destination::User(id) => format!("<@{}>", id),
+            Destination::UserGroup(id) => format!("<!subteam^{}>", id),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Validation messages without the config-file framing.
+fn problems(error: ConfigError) -> String {
+    match error {
+        ConfigError::Invalid(errors) => errors
+            .iter()
+            .map(|e| e.message.clone())
+            .collect::<Vec<_>>()
+            .join("; "),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_subcommands() {
+        assert_eq!(parse(""), Ok(Command::Help));
+        assert_eq!(parse(" HELP "), Ok(Command::Help));
+        assert_eq!(parse("list"), Ok(Command::List));
+        assert_eq!(
+            parse("pause standup"),
+            Ok(Command::Pause("standup".to_string()))
+        );
+        assert_eq!(
+            parse("Resume standup"),
+            Ok(Command::Resume("standup".to_string()))
+        );
+        assert_eq!(
+            parse("delete standup"),
+            Ok(Command::Delete("standup".to_string()))
+        );
+        assert_eq!(parse("next"), Ok(Command::Next(None)));
+        assert_eq!(
+            parse("next standup"),
+            Ok(Command::Next(Some("standup".to_string())))
+        );
+
+        assert!(parse("pause")
+            .unwrap_err()
+            .starts_with("`pause` takes one reminder name."));
+        assert!(parse("delete two names").is_err());
+        assert!(parse("snooze standup")
+            .unwrap_err()
+            .starts_with("Unknown command `snooze`."));
+    }
+
+    #[test]
+    fn parses_add() {
+        let add = |schedule: &str, destination, text: &str| {
+            Ok(Command::Add {
+                schedule: schedule.to_string(),
+                destination,
+                text: text.to_string(),
+            })
+        };
+        assert_eq!(
+            parse("add \"every Monday at 9am\" <#C123|standup> Standup time!"),
+            add(
+                "every Monday at 9am",
+                Some(Destination::Channel("C123".to_string())),
+                "Standup time!"
+            )
+        );
+        assert_eq!(
+            parse("add “every day at 5pm” Go home"),
+            add("every day at 5pm", None, "Go home")
+        );
+        assert_eq!(
+            parse("add \"0 0 9 * * *\" <@U123> hi"),
+            add(
+                "0 0 9 * * *",
+                Some(Destination::User("U123".to_string())),
+                "hi"
+            )
+        );
+        assert_eq!(
+            parse("add \"at 2026-11-03 15:00\" <!subteam^S123> Freeze"),
+            add(
+                "at 2026-11-03 15:00",
+                Some(Destination::UserGroup("S123".to_string())),
+                "Freeze"
+            )
+        );
+        assert_eq!(
+            parse("add \"every 90m\" #random @lead@example.com"),
+            add(
+                "every 90m",
+                Some(Destination::Channel("#random".to_string())),
+                "@lead@example.com"
+            )
+        );
+        // A lone @word is text, not a destination
+        assert_eq!(
+            parse("add \"every 90m\" @here stretch"),
+            add("every 90m", None, "@here stretch")
+        );
+
+        assert!(parse("add every day hi")
+            .unwrap_err()
+            .starts_with("Put the schedule in quotes."));
+        assert!(parse("add \"every day\" <#C123>")
+            .unwrap_err()
+            .starts_with("The reminder text is missing."));
+        assert!(parse("add \"every day").is_err());
+    }
+}
@@ -0,0 +1,1508 @@
+use crate::blocks;
+use crate::calendar::{Blackout, Calendar};
+use crate::jitter::Jitter;
+use crate::names::{self, DEFAULT_NAMES_CACHE_PATH};
+use crate::retry::RetryPolicy;
+use crate::rrule::Rule;
+use crate::schedule::{self, Schedule};
+use crate::signature;
+use crate::state::DEFAULT_STATE_PATH;
+use crate::template;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Default location of the reminder config when neither the CLI flag nor the
+/// environment variable is provided.
+pub const DEFAULT_CONFIG_PATH: &str = "reminders.toml";
+
+/// Grace period for in-flight sends when the process is asked to stop.
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+
+/// How often the config file is checked for edits.
+const DEFAULT_WATCH_INTERVAL_SECS: u64 = 5;
+
+/// Workspace used when the config declares none; its token comes from
+/// `SLACK_BOT_TOKEN`.
+pub const DEFAULT_WORKSPACE: &str = "default";
+const DEFAULT_TOKEN_ENV: &str = "SLACK_BOT_TOKEN";
+
+/// HTTP client and rate limiter defaults for a workspace.
+const DEFAULT_HTTP_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_REQUESTS_PER_MINUTE: u32 = 60;
+
+/// Longest span a minutes or hours setting such as `catch_up_grace_minutes`
+/// may cover.
+const MAX_SPAN_DAYS: i64 = 366;
+
+/// Raw shape of the config file, before validation.
+#[derive(Debug, Deserialize)]
+struct RawConfig {
+    #[serde(default)]
+    retry: RawRetry,
+    #[serde(default)]
+    state: RawState,
+    #[serde(default)]
+    shutdown: RawShutdown,
+    #[serde(default)]
+    reload: RawReload,
+    /// `[vars]`: template variables shared by every reminder.
+    #[serde(default)]
+    vars: BTreeMap<String, String>,
+    #[serde(default, rename = "workspace")]
+    workspaces: Vec<RawWorkspace>,
+    #[serde(default, rename = "calendar")]
+    calendars: Vec<RawCalendar>,
+    /// `[commands]`: the `/remind-bot` slash command endpoint, off if absent.
+    commands: Option<RawCommands>,
+    #[serde(default, rename = "reminder")]
+    reminders: Vec<RawReminder>,
+}
+
+/// `[[workspace]]` entry: a Slack workspace and where its bot token comes
+/// from.
+#[derive(Debug, Deserialize)]
+struct RawWorkspace {
+    name: String,
+    token_env: Option<String>,
+    token_file: Option<PathBuf>,
+    timeout_secs: Option<u64>,
+    connect_timeout_secs: Option<u64>,
+    requests_per_minute: Option<u32>,
+}
+
+/// `[[calendar]]` entry: days reminders can be kept off, from an iCalendar
+/// file and/or listed inline.
+#[derive(Debug, Deserialize)]
+struct RawCalendar {
+    name: String,
+    ics: Option<PathBuf>,
+    /// `"2026-12-24"` or an inclusive range `"2026-12-24..2027-01-01"`.
+    #[serde(default)]
+    dates: Vec<String>,
+}
+
+/// `[retry]` section; every field falls back to [`RetryPolicy::default`].
+#[derive(Debug, Default, Deserialize)]
+struct RawRetry {
+    max_attempts: Option<u32>,
+    base_delay_ms: Option<u64>,
+    max_delay_ms: Option<u64>,
+    jitter: Option<f64>,
+    deadline_secs: Option<u64>,
+}
+
+/// `[state]` section.
+#[derive(Debug, Default, Deserialize)]
+struct RawState {
+    path: Option<PathBuf>,
+    /// Where resolved `#channel` / `@email` references are cached.
+    names_cache: Option<PathBuf>,
+}
+
+/// `[shutdown]` section.
+#[derive(Debug, Default, Deserialize)]
+struct RawShutdown {
+    timeout_secs: Option<u64>,
+}
+
+/// `[reload]` section.
+#[derive(Debug, Default, Deserialize)]
+struct RawReload {
+    watch_interval_secs: Option<u64>,
+}
+
+/// `[commands]` section.
+#[derive(Debug, Deserialize)]
+struct RawCommands {
+    /// Serve slash commands over HTTP on this address.
+    listen: Option<String>,
+    signing_secret_env: Option<String>,
+    signing_secret_file: Option<PathBuf>,
+    /// The secret being rotated out, still accepted until removed.
+    previous_signing_secret_env: Option<String>,
+    previous_signing_secret_file: Option<PathBuf>,
+    signature_tolerance_secs: Option<u64>,
+    /// App-level token (`xapp-`) for Socket Mode, which needs no public URL.
+    app_token_env: Option<String>,
+    app_token_file: Option<PathBuf>,
+    workspace: Option<String>,
+    timezone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawReminder {
+    name: String,
+    /// Name of a `[[workspace]]`; optional when only one is declared.
+    workspace: Option<String>,
+    /// Exactly one of `cron`, `at`, `every`, `rrule` and `schedule` sets the
+    /// schedule.
+    cron: Option<String>,
+    /// Fire once, at this wall-clock time in `timezone`.
+    at: Option<String>,
+    /// Fire at a fixed interval such as "90m", from `anchor` if set.
+    every: Option<String>,
+    /// An RFC 5545 recurrence rule such as "FREQ=WEEKLY;INTERVAL=2;BYDAY=SU",
+    /// anchored at `anchor` if set.
+    rrule: Option<String>,
+    /// Where `every` and `rrule` count from; not to be confused with
+    /// `starts_at`.
+    anchor: Option<String>,
+    /// Any form `Schedule` parses, including phrases like "every weekday at
+    /// 9:30".
+    schedule: Option<String>,
+    channel: Option<String>,
+    /// Channel IDs or `#channel-name` references.
+    #[serde(default)]
+    channels: Vec<String>,
+    /// User IDs or `@email` references, each messaged in a DM opened via
+    /// `conversations.open`.
+    #[serde(default)]
+    users: Vec<String>,
+    /// User group IDs, expanded to a DM per member.
+    #[serde(default)]
+    usergroups: Vec<String>,
+    /// Required; a missing `text` is reported like an empty one.
+    #[serde(default)]
+    text: String,
+    timezone: Option<String>,
+    catch_up: Option<String>,
+    catch_up_grace_minutes: Option<i64>,
+    /// Block Kit blocks, as TOML tables or a JSON string.
+    blocks: Option<serde_json::Value>,
+    /// Legacy attachments, as TOML tables or a JSON string.
+    attachments: Option<serde_json::Value>,
+    /// Template variables, overriding `[vars]` entries of the same name.
+    #[serde(default)]
+    vars: BTreeMap<String, String>,
+    /// Reply in the thread of this reminder's latest post (may be itself).
+    thread_under: Option<String>,
+    reply_broadcast: Option<bool>,
+    thread_max_age_hours: Option<i64>,
+    /// Add "Done" and "Snooze" buttons to each post.
+    #[serde(default)]
+    buttons: bool,
+    ack: Option<RawAck>,
+    /// Names of `[[calendar]]` entries whose days the reminder avoids.
+    #[serde(default)]
+    calendars: Vec<String>,
+    on_holiday: Option<String>,
+    /// Wall-clock times in `timezone` bounding when the reminder posts.
+    starts_at: Option<String>,
+    ends_at: Option<String>,
+    /// Deactivate the reminder after this many posted occurrences.
+    max_occurrences: Option<u64>,
+    /// Post this reminder up to this many seconds late, by the same amount
+    /// every time.
+    jitter_seconds: Option<u64>,
+    /// Post each occurrence at some point within this many minutes of its
+    /// scheduled time.
+    fire_within_minutes: Option<u64>,
+}
+
+/// `[reminder.ack]`: require acknowledgement and escalate without it.
+#[derive(Debug, Deserialize)]
+struct RawAck {
+    within_minutes: i64,
+    /// Reaction names that count; any reaction does when empty.
+    #[serde(default)]
+    reactions: Vec<String>,
+    #[serde(default)]
+    escalate: Vec<Escalation>,
+}
+
+/// Somewhere a reminder is posted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Destination {
+    Channel(String),
+    /// Direct message to a user, opened with `conversations.open`.
+    User(String),
+    /// Direct message to every member of a user group.
+    UserGroup(String),
+}
+
+impl Destination {
+    /// Stable identifier used in the delivery ledger and for threading.
+    pub fn key(&self) -> String {
+        match self {
+            Destination::Channel(id) => format!("channel:{}", id),
+            Destination::User(id) => format!("user:{}", id),
+            Destination::UserGroup(id) => format!("usergroup:{}", id),
+        }
+    }
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Destination::Channel(id) => write!(f, "channel {}", id),
+            Destination::User(id) => write!(f, "user {}", id),
+            Destination::UserGroup(id) => write!(f, "user group {}", id),
+        }
+    }
+}
+
+/// How a reminder threads under an earlier post.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thread {
+    /// Reminder whose latest post is the thread parent.
+    pub parent: String,
+    /// Also show the reply in the channel.
+    pub broadcast: bool,
+    /// Post top-level instead when the parent is older than this.
+    pub max_age: Option<chrono::Duration>,
+}
+
+/// Acknowledgement a reminder's posts need, and what happens without it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ack {
+    /// Time allowed before the first escalation step, and between steps.
+    pub within: chrono::Duration,
+    /// Reaction names (without colons) that acknowledge; empty means any.
+    pub reactions: Vec<String>,
+    pub escalate: Vec<Escalation>,
+}
+
+/// One escalation step, written as `"repost"`, `{ dm = "U…" }` or
+/// `{ usergroup = "S…" }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Escalation {
+    /// Post the reminder again where it was posted.
+    Repost,
+    /// DM a user (ID or `@email`) about it.
+    Dm(String),
+    /// Mention a user group where it was posted.
+    Usergroup(String),
+}
+
+impl fmt::Display for Escalation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Escalation::Repost => write!(f, "re-post"),
+            Escalation::Dm(user) => write!(f, "DM {}", user),
+            Escalation::Usergroup(group) => write!(f, "ping user group {}", group),
+        }
+    }
+}
+
+/// What to do on startup with occurrences that passed while the bot was down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatchUp {
+    /// Drop missed occurrences and wait for the next one.
+    Skip,
+    /// Post once, late, if anything was missed within the grace window.
+    Once,
+    /// Post every occurrence missed within the grace window.
+    All,
+}
+
+impl FromStr for CatchUp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(CatchUp::Skip),
+            "once" => Ok(CatchUp::Once),
+            "all" => Ok(CatchUp::All),
+            other => Err(format!(
+                "unknown catch-up policy '{}', expected skip, once or all",
+                other
+            )),
+        }
+    }
+}
+
+/// What happens to an occurrence that falls on a day in one of the
+/// reminder's calendars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HolidayPolicy {
+    Skip,
+    /// Post on the next weekday that is not in a calendar, at the same time.
+    NextBusinessDay,
+    /// Post on the previous weekday that is not in a calendar.
+    PreviousBusinessDay,
+}
+
+impl FromStr for HolidayPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(HolidayPolicy::Skip),
+            "next_business_day" => Ok(HolidayPolicy::NextBusinessDay),
+            "previous_business_day" => Ok(HolidayPolicy::PreviousBusinessDay),
+            other => Err(format!(
+                "unknown holiday policy '{}', expected skip, next_business_day or previous_business_day",
+                other
+            )),
+        }
+    }
+}
+
+/// Where a workspace's bot token is read from at startup.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenSource {
+    Env(String),
+    File(PathBuf),
+}
+
+impl TokenSource {
+    pub fn load(&self) -> Result<String, String> {
+        let token = match self {
+            TokenSource::Env(var) => {
+                env::var(var).map_err(|_| format!("{} is not set in the environment", var))?
+            }
+            TokenSource::File(path) => fs::read_to_string(path)
+                .map_err(|e| format!("failed to read token file {}: {}", path.display(), e))?,
+        };
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(format!("{} is empty", self));
+        }
+        Ok(token.to_string())
+    }
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::Env(var) => write!(f, "${}", var),
+            TokenSource::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// A Slack workspace with its own token, HTTP client and rate limit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub name: String,
+    pub token: TokenSource,
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    pub requests_per_minute: u32,
+}
+
+/// HTTP endpoint for Slack requests, verified with the app's signing secret.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpSettings {
+    pub listen: SocketAddr,
+    pub signing_secret: TokenSource,
+    /// Also accepted while rotating secrets.
+    pub previous_signing_secret: Option<TokenSource>,
+    /// How far a request's timestamp may be from now.
+    pub signature_tolerance: Duration,
+}
+
+/// Where `/remind-bot` requests are accepted (HTTP, Socket Mode or both) and
+/// how new reminders are set up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandSettings {
+    pub http: Option<HttpSettings>,
+    /// App-level token for Socket Mode.
+    pub app_token: Option<TokenSource>,
+    /// Workspace the slash command is installed in.
+    pub workspace: String,
+    /// Zone cron expressions given to `add` are evaluated in.
+    pub timezone: Tz,
+}
+
+/// A validated reminder, ready to be handed to `run_schedule`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub name: String,
+    pub workspace: String,
+    pub schedule: Schedule,
+    pub destinations: Vec<Destination>,
+    pub text: String,
+    /// Zone the cron fields are evaluated in; UTC unless configured.
+    pub timezone: Tz,
+    pub catch_up: CatchUp,
+    /// How far back missed occurrences are still worth posting.
+    pub catch_up_grace: chrono::Duration,
+    /// Optional Block Kit layout; `text` stays as the notification fallback.
+    pub blocks: Option<serde_json::Value>,
+    pub attachments: Option<serde_json::Value>,
+    /// User-defined template variables (`[vars]` merged with the reminder's).
+    pub vars: BTreeMap<String, String>,
+    pub thread: Option<Thread>,
+    /// Whether posts carry "Done" and "Snooze" buttons.
+    pub buttons: bool,
+    pub ack: Option<Ack>,
+    /// Days from the reminder's calendars, and what to do on them.
+    pub blackout: Option<Blackout>,
+    /// Nothing is posted for occurrences before `starts_at` or after
+    /// `ends_at`; the reminder ends once `ends_at` has passed.
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// The reminder ends once this many occurrences have been posted, as
+    /// counted in the state file.
+    pub max_occurrences: Option<u64>,
+    /// Delay after the scheduled time before posting.
+    pub jitter: Option<Jitter>,
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub settings: Settings,
+    pub reminders: Vec<Reminder>,
+}
+
+/// Process-wide settings. Unlike reminders these are only read at startup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub retry: RetryPolicy,
+    pub state_path: PathBuf,
+    pub names_cache_path: PathBuf,
+    pub workspaces: Vec<Workspace>,
+    pub commands: Option<CommandSettings>,
+    /// How long in-flight sends get to finish after SIGTERM/SIGINT.
+    pub shutdown_timeout: Duration,
+    /// How often the config file is checked for changes; zero disables it.
+    pub watch_interval: Duration,
+}
+
+/// A single problem found while validating the config.
+#[derive(Debug)]
+pub struct ValidationError {
+    /// Where the problem is, e.g. `reminder 'standup'` or `[retry]`.
+    pub entry: String,
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, field '{}': {}",
+            self.entry, self.field, self.message
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(Vec<ValidationError>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "failed to parse {}: {}", path.display(), e),
+            ConfigError::Invalid(errors) => {
+                write!(f, "invalid reminder configuration:")?;
+                for error in errors {
+                    write!(f, "\n  - {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Resolve the config path from `--config <path>` / `--config=<path>`, then
+/// `REMINDER_CONFIG`, then [`DEFAULT_CONFIG_PATH`].
+pub fn config_path_from_args(args: impl IntoIterator<Item = String>) -> PathBuf {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            if let Some(path) = args.next() {
+                return PathBuf::from(path);
+            }
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            return PathBuf::from(path);
+        }
+    }
+    std::env::var("REMINDER_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_CONFIG_PATH))
+}
+
+/// Command-line arguments other than `--config` and its value.
+pub fn positional_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            args.next();
+        } else if !arg.starts_with("--config=") {
+            positional.push(arg);
+        }
+    }
+    positional
+}
+
+/// Load and validate the config file. `default_channel` is used for entries
+/// that set no destination of their own.
+pub fn load(path: &Path, default_channel: Option<&str>) -> Result<Config, ConfigError> {
+    let contents = fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+    let raw: RawConfig =
+        toml::from_str(&contents).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))?;
+    validate(raw, default_channel)
+}
+
+fn validate(raw: RawConfig, default_channel: Option<&str>) -> Result<Config, ConfigError> {
+    let mut errors = Vec::new();
+    let mut reminders = Vec::new();
+    let mut seen = HashSet::new();
+
+    let retry = validate_retry(&raw.retry, &mut errors);
+    let workspaces = validate_workspaces(raw.workspaces, &mut errors);
+    let calendars = validate_calendars(raw.calendars, &mut errors);
+    let commands = raw
+        .commands
+        .and_then(|commands| validate_commands(commands, &workspaces, &mut errors));
+    for name in raw.vars.keys() {
+        if template::BUILTIN_VARS.contains(&name.as_str()) {
+            errors.push(ValidationError {
+                entry: "[vars]".to_string(),
+                field: "vars",
+                message: format!("'{}' shadows a built-in template variable", name),
+            });
+        }
+    }
+
+    for (index, entry) in raw.reminders.into_iter().enumerate() {
+        let name = if entry.name.trim().is_empty() {
+            let placeholder = format!("#{}", index + 1);
+            errors.push(ValidationError {
+                entry: format!("reminder {}", placeholder),
+                field: "name",
+                message: "must not be empty".to_string(),
+            });
+            placeholder
+        } else {
+            entry.name.clone()
+        };
+        let mut invalid = |field, message: String| {
+            errors.push(ValidationError {
+                entry: format!("reminder '{}'", name),
+                field,
+                message,
+            })
+        };
+
+        if !seen.insert(entry.name.clone()) {
+            invalid("name", "duplicate reminder name".to_string());
+        }
+
+        let workspace = match (entry.workspace, workspaces.as_slice()) {
+            (Some(workspace), _) if workspaces.iter().any(|w| w.name == workspace) => {
+                Some(workspace)
+            }
+            (Some(workspace), _) => {
+                invalid("workspace", format!("no workspace named '{}'", workspace));
+                None
+            }
+            (None, [only]) => Some(only.name.clone()),
+            (None, _) => {
+                invalid(
+                    "workspace",
+                    "required when more than one workspace is declared".to_string(),
+                );
+                None
+            }
+        };
+
+        let anchored = entry.every.is_some() || entry.rrule.is_some();
+        let start = entry
+            .anchor
+            .as_deref()
+            .map(schedule::parse_datetime)
+            .transpose()
+            .map_err(|e| invalid("anchor", e));
+        let schedule = match (
+            entry.cron,
+            entry.at,
+            entry.every,
+            entry.rrule,
+            entry.schedule,
+        ) {
+            (Some(cron), None, None, None, None) => match cron::Schedule::from_str(&cron) {
+                Ok(schedule) => Some(Schedule::Cron(Box::new(schedule))),
+                Err(e) => {
+                    invalid("cron", format!("invalid cron expression '{}': {}", cron, e));
+                    None
+                }
+            },
+            (None, Some(at), None, None, None) => match schedule::parse_datetime(&at) {
+                Ok(at) => Some(Schedule::Once(at)),
+                Err(e) => {
+                    invalid("at", e);
+                    None
+                }
+            },
+            (None, None, Some(every), None, None) => match schedule::parse_interval(&every) {
+                Ok(every) => start.ok().map(|start| Schedule::Every { every, start }),
+                Err(e) => {
+                    invalid("every", e);
+                    None
+                }
+            },
+            (None, None, None, Some(rule), None) => {
+                let text = match start {
+                    Ok(Some(_)) if rule.to_ascii_uppercase().contains("DTSTART") => {
+                        invalid("anchor", "the rrule already has a DTSTART".to_string());
+                        None
+                    }
+                    Ok(Some(start)) => Some(format!(
+                        "DTSTART:{} {}",
+                        start.format("%Y%m%dT%H%M%S"),
+                        rule
+                    )),
+                    Ok(None) => Some(rule),
+                    Err(()) => None,
+                };
+                match text.map(|text| Rule::from_str(&text)) {
+                    Some(Ok(rule)) => Some(Schedule::Rule(Box::new(rule))),
+                    Some(Err(e)) => {
+                        invalid("rrule", e);
+                        None
+                    }
+                    None => None,
+                }
+            }
+            (None, None, None, None, Some(text)) => match Schedule::from_str(&text) {
+                Ok(schedule) => Some(schedule),
+                Err(e) => {
+                    invalid("schedule", e);
+                    None
+                }
+            },
+            _ => {
+                invalid(
+                    "cron",
+                    "set exactly one of cron, at, every, rrule or schedule".to_string(),
+                );
+                None
+            }
+        };
+        if entry.anchor.is_some() && !anchored {
+            invalid("anchor", "only applies to every and rrule".to_string());
+        }
+
+        let mut destinations = Vec::new();
+        let channels = entry.channel.into_iter().chain(entry.channels);
+        let targets = channels
+            .map(|id| ("channels", Destination::Channel(id)))
+            .chain(
+                entry
+                    .users
+                    .into_iter()
+                    .map(|id| ("users", Destination::User(id))),
+            )
+            .chain(
+                entry
+                    .usergroups
+                    .into_iter()
+                    .map(|id| ("usergroups", Destination::UserGroup(id))),
+            );
+        for (field, destination) in targets {
+            let (Destination::Channel(id) | Destination::User(id) | Destination::UserGroup(id)) =
+                &destination;
+            if id.trim().is_empty() {
+                invalid(field, "entries must not be empty".to_string());
+            } else if let Err(message) = names::check_reference(&destination) {
+                invalid(field, message);
+            } else if destinations.contains(&destination) {
+                invalid(field, format!("{} is listed twice", destination));
+            } else {
+                destinations.push(destination);
+            }
+        }
+        if destinations.is_empty() {
+            match default_channel {
+                Some(channel) if !channel.trim().is_empty() => {
+                    destinations.push(Destination::Channel(channel.to_string()))
+                }
+                _ => invalid(
+                    "channels",
+                    "no channel, user or user group set and SLACK_CHANNEL_ID is not available"
+                        .to_string(),
+                ),
+            }
+        }
+
+        let mut vars = raw.vars.clone();
+        for (key, value) in entry.vars {
+            if template::BUILTIN_VARS.contains(&key.as_str()) {
+                invalid(
+                    "vars",
+                    format!("'{}' shadows a built-in template variable", key),
+                );
+            }
+            vars.insert(key, value);
+        }
+
+        if let Err(e) = template::validate(&entry.text, &vars) {
+            invalid("text", e);
+        }
+
+        if entry.text.trim().is_empty() {
+            invalid("text", "must not be empty".to_string());
+        } else if entry.text.chars().count() > blocks::MAX_TEXT_LEN {
+            invalid(
+                "text",
+                format!("longer than {} characters", blocks::MAX_TEXT_LEN),
+            );
+        }
+
+        // Buttons need blocks; without any, the text becomes a section.
+        // Acknowledgement needs the "Done" button.
+        let buttons = entry.buttons || entry.ack.is_some();
+        if buttons
+            && entry.blocks.is_none()
+            && entry.text.chars().count() > blocks::MAX_SECTION_TEXT_LEN
+        {
+            invalid(
+                "text",
+                format!(
+                    "longer than {} characters, the limit with buttons and no blocks",
+                    blocks::MAX_SECTION_TEXT_LEN
+                ),
+            );
+        }
+
+        let blocks = entry
+            .blocks
+            .map(blocks::normalize)
+            .and_then(|parsed| match parsed {
+                Ok(value) => {
+                    for error in blocks::validate_blocks(&value) {
+                        invalid("blocks", error);
+                    }
+                    if buttons
+                        && value
+                            .as_array()
+                            .is_some_and(|b| b.len() >= blocks::MAX_BLOCKS)
+                    {
+                        invalid(
+                            "blocks",
+                            format!(
+                                "{} blocks leave no room for the buttons",
+                                blocks::MAX_BLOCKS
+                            ),
+                        );
+                    }
+                    for text in template::strings_in(&value) {
+                        if let Err(e) = template::validate(text, &vars) {
+                            invalid("blocks", e);
+                        }
+                    }
+                    Some(value)
+                }
+                Err(e) => {
+                    invalid("blocks", e);
+                    None
+                }
+            });
+
+        let attachments =
+            entry
+                .attachments
+                .map(blocks::normalize)
+                .and_then(|parsed| match parsed {
+                    Ok(value) => {
+                        for error in blocks::validate_attachments(&value) {
+                            invalid("attachments", error);
+                        }
+                        for text in template::strings_in(&value) {
+                            if let Err(e) = template::validate(text, &vars) {
+                                invalid("attachments", e);
+                            }
+                        }
+                        Some(value)
+                    }
+                    Err(e) => {
+                        invalid("attachments", e);
+                        None
+                    }
+                });
+
+        let timezone = match entry.timezone.as_deref().map(Tz::from_str) {
+            None => Some(Tz::UTC),
+            Some(Ok(tz)) => Some(tz),
+            Some(Err(_)) => {
+                invalid(
+                    "timezone",
+                    format!(
+                        "unknown IANA time zone '{}'",
+                        entry.timezone.as_deref().unwrap_or_default()
+                    ),
+                );
+                None
+            }
+        };
+
+        let catch_up = match entry.catch_up.as_deref().map(CatchUp::from_str) {
+            None => Some(CatchUp::Skip),
+            Some(Ok(catch_up)) => Some(catch_up),
+            Some(Err(e)) => {
+                invalid("catch_up", e);
+                None
+            }
+        };
+
+        let policy = match entry.on_holiday.as_deref().map(HolidayPolicy::from_str) {
+            None => Some(HolidayPolicy::Skip),
+            Some(Ok(policy)) => Some(policy),
+            Some(Err(e)) => {
+                invalid("on_holiday", e);
+                None
+            }
+        };
+        if entry.on_holiday.is_some() && entry.calendars.is_empty() {
+            invalid("on_holiday", "only applies with calendars".to_string());
+        }
+        let mut days = BTreeMap::new();
+        for name in &entry.calendars {
+            match calendars.get(name) {
+                Some(calendar) => {
+                    for (date, label) in &calendar.days {
+                        days.entry(*date).or_insert_with(|| label.clone());
+                    }
+                }
+                None => invalid("calendars", format!("no calendar named '{}'", name)),
+            }
+        }
+        let blackout = match policy {
+            Some(policy) if !entry.calendars.is_empty() => Some(Blackout { days, policy }),
+            _ => None,
+        };
+
+        let thread = match entry.thread_under {
+            Some(parent) => {
+                let max_age = entry.thread_max_age_hours.and_then(|hours| {
+                    bounded_span(hours, chrono::Duration::try_hours)
+                        .map_err(|e| invalid("thread_max_age_hours", e))
+                        .ok()
+                });
+                Some(Thread {
+                    parent,
+                    broadcast: entry.reply_broadcast.unwrap_or(false),
+                    max_age,
+                })
+            }
+            None => {
+                if entry.reply_broadcast.is_some() {
+                    invalid("reply_broadcast", "requires thread_under".to_string());
+                }
+                if entry.thread_max_age_hours.is_some() {
+                    invalid("thread_max_age_hours", "requires thread_under".to_string());
+                }
+                None
+            }
+        };
+
+        let ack =
+            entry.ack.map(|raw| {
+                let within = bounded_span(raw.within_minutes, chrono::Duration::try_minutes)
+                    .map_err(|e| invalid("ack.within_minutes", e))
+                    .unwrap_or_default();
+                for reaction in &raw.reactions {
+                    if reaction.trim_matches(':').is_empty()
+                        || reaction.contains(char::is_whitespace)
+                    {
+                        invalid(
+                            "ack.reactions",
+                            format!("'{}' is not an emoji name", reaction),
+                        );
+                    }
+                }
+                for step in &raw.escalate {
+                    let destination = match step {
+                        Escalation::Repost => continue,
+                        Escalation::Dm(user) => Destination::User(user.clone()),
+                        Escalation::Usergroup(group) => Destination::UserGroup(group.clone()),
+                    };
+                    let (Destination::User(id)
+                    | Destination::UserGroup(id)
+                    | Destination::Channel(id)) = &destination;
+                    if id.trim().is_empty() {
+                        invalid("ack.escalate", "entries must not be empty".to_string());
+                    } else if let Err(message) = names::check_reference(&destination) {
+                        invalid("ack.escalate", message);
+                    }
+                }
+                Ack {
+                    within,
+                    reactions: raw
+                        .reactions
+                        .iter()
+                        .map(|reaction| reaction.trim_matches(':').to_string())
+                        .collect(),
+                    escalate: raw.escalate,
+                }
+            });
+
+        let mut bound = |field, text: Option<String>| {
+            let local = schedule::parse_datetime(&text?)
+                .map_err(|e| invalid(field, e))
+                .ok()?;
+            timezone.map(|tz| schedule::resolve_local(tz, local))
+        };
+        let starts_at = bound("starts_at", entry.starts_at);
+        let ends_at = bound("ends_at", entry.ends_at);
+        if let (Some(starts_at), Some(ends_at)) = (starts_at, ends_at) {
+            if ends_at <= starts_at {
+                invalid("ends_at", "must be after starts_at".to_string());
+            }
+        }
+        if entry.max_occurrences == Some(0) {
+            invalid("max_occurrences", "must be positive".to_string());
+        }
+
+        let jitter = match (entry.jitter_seconds, entry.fire_within_minutes) {
+            (None, None) => None,
+            (Some(0), None) => {
+                invalid("jitter_seconds", "must be positive".to_string());
+                None
+            }
+            (Some(seconds), None) if seconds > MAX_SPAN_DAYS as u64 * 86_400 => {
+                invalid(
+                    "jitter_seconds",
+                    format!("must not exceed {} days", MAX_SPAN_DAYS),
+                );
+                None
+            }
+            (Some(seconds), None) => Some(Jitter::Fixed(Duration::from_secs(seconds))),
+            (None, Some(0)) => {
+                invalid("fire_within_minutes", "must be positive".to_string());
+                None
+            }
+            (None, Some(minutes)) => match minutes
+                .checked_mul(60)
+                .filter(|seconds| *seconds <= MAX_SPAN_DAYS as u64 * 86_400)
+            {
+                Some(seconds) => Some(Jitter::Window(Duration::from_secs(seconds))),
+                None => {
+                    invalid(
+                        "fire_within_minutes",
+                        format!("must not exceed {} days", MAX_SPAN_DAYS),
+                    );
+                    None
+                }
+            },
+            (Some(_), Some(_)) => {
+                invalid(
+                    "jitter_seconds",
+                    "set jitter_seconds or fire_within_minutes, not both".to_string(),
+                );
+                None
+            }
+        };
+
+        let catch_up_grace = bounded_span(
+            entry.catch_up_grace_minutes.unwrap_or(24 * 60),
+            chrono::Duration::try_minutes,
+        )
+        .map_err(|e| invalid("catch_up_grace_minutes", e))
+        .ok();
+
+        if let (
+            Some(workspace),
+            Some(schedule),
+            Some(timezone),
+            Some(catch_up),
+            Some(catch_up_grace),
+        ) = (workspace, schedule, timezone, catch_up, catch_up_grace)
+        {
+            reminders.push(Reminder {
+                name,
+                workspace,
+                schedule,
+                destinations,
+                text: entry.text,
+                timezone,
+                catch_up,
+                catch_up_grace,
+                blocks,
+                attachments,
+                vars,
+                thread,
+                buttons,
+                ack,
+                blackout,
+                starts_at,
+                ends_at,
+                max_occurrences: entry.max_occurrences,
+                jitter,
+            });
+        }
+    }
+
+    // Thread parents can only be checked once every name is known
+    for reminder in &reminders {
+        if let Some(thread) = &reminder.thread {
+            if !seen.contains(&thread.parent) {
+                errors.push(ValidationError {
+                    entry: format!("reminder '{}'", reminder.name),
+                    field: "thread_under",
+                    message: format!("no reminder named '{}'", thread.parent),
+                });
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(Config {
+            settings: Settings {
+                retry,
+                state_path: raw
+                    .state
+                    .path
+                    .unwrap_or_else(|| PathBuf::from(DEFAULT_STATE_PATH)),
+                names_cache_path: raw
+                    .state
+                    .names_cache
+                    .unwrap_or_else(|| PathBuf::from(DEFAULT_NAMES_CACHE_PATH)),
+                workspaces,
+                commands,
+                shutdown_timeout: Duration::from_secs(
+                    raw.shutdown
+                        .timeout_secs
+                        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS),
+                ),
+                watch_interval: Duration::from_secs(
+                    raw.reload
+                        .watch_interval_secs
+                        .unwrap_or(DEFAULT_WATCH_INTERVAL_SECS),
+                ),
+            },
+            reminders,
+        })
+    } else {
+        Err(ConfigError::Invalid(errors))
+    }
+}
+
+/// A positive `amount` of minutes or hours, converted by `unit` (such as
+/// `chrono::Duration::try_minutes`), of at most `MAX_SPAN_DAYS`.
+fn bounded_span(
+    amount: i64,
+    unit: fn(i64) -> Option<chrono::Duration>,
+) -> Result<chrono::Duration, String> {
+    if amount <= 0 {
+        return Err("must be positive".to_string());
+    }
+    unit(amount)
+        .filter(|span| *span <= chrono::Duration::days(MAX_SPAN_DAYS))
+        .ok_or_else(|| format!("must not exceed {} days", MAX_SPAN_DAYS))
+}
+
+/// Load each `[[calendar]]`'s file and dates, by name.
+fn validate_calendars(
+    raw: Vec<RawCalendar>,
+    errors: &mut Vec<ValidationError>,
+) -> BTreeMap<String, Calendar> {
+    let mut calendars = BTreeMap::new();
+    for entry in raw {
+        let mut invalid = |field, message: String| {
+            errors.push(ValidationError {
+                entry: format!("calendar '{}'", entry.name),
+                field,
+                message,
+            })
+        };
+        if entry.name.trim().is_empty() {
+            invalid("name", "must not be empty".to_string());
+        } else if calendars.contains_key(&entry.name) {
+            invalid("name", "duplicate calendar name".to_string());
+        }
+        if entry.ics.is_none() && entry.dates.is_empty() {
+            invalid("dates", "set ics, dates or both".to_string());
+        }
+        let mut calendar = Calendar::default();
+        if let Some(path) = &entry.ics {
+            if let Err(e) = calendar.add_ics(path, &entry.name) {
+                invalid("ics", e);
+            }
+        }
+        for dates in &entry.dates {
+            if let Err(e) = calendar.add_dates(dates, &entry.name) {
+                invalid("dates", e);
+            }
+        }
+        calendars.entry(entry.name).or_insert(calendar);
+    }
+    calendars
+}
+
+/// Without `[[workspace]]` entries a single default workspace reads its token
+/// from `SLACK_BOT_TOKEN`.
+fn validate_workspaces(
+    raw: Vec<RawWorkspace>,
+    errors: &mut Vec<ValidationError>,
+) -> Vec<Workspace> {
+    if raw.is_empty() {
+        return vec![Workspace {
+            name: DEFAULT_WORKSPACE.to_string(),
+            token: TokenSource::Env(DEFAULT_TOKEN_ENV.to_string()),
+            timeout: Duration::from_secs(DEFAULT_HTTP_TIMEOUT_SECS),
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+            requests_per_minute: DEFAULT_REQUESTS_PER_MINUTE,
+        }];
+    }
+
+    let mut workspaces: Vec<Workspace> = Vec::new();
+    for entry in raw {
+        let mut invalid = |field, message: &str| {
+            errors.push(ValidationError {
+                entry: format!("workspace '{}'", entry.name),
+                field,
+                message: message.to_string(),
+            })
+        };
+        if entry.name.trim().is_empty() {
+            invalid("name", "must not be empty");
+        } else if workspaces.iter().any(|w| w.name == entry.name) {
+            invalid("name", "duplicate workspace name");
+        }
+        let token = match (entry.token_env, entry.token_file) {
+            (Some(var), None) if !var.trim().is_empty() => Some(TokenSource::Env(var)),
+            (None, Some(path)) => Some(TokenSource::File(path)),
+            (Some(_), Some(_)) => {
+                invalid("token_env", "set either token_env or token_file, not both");
+                None
+            }
+            _ => {
+                invalid("token_env", "set token_env or token_file");
+                None
+            }
+        };
+        let timeout = entry.timeout_secs.unwrap_or(DEFAULT_HTTP_TIMEOUT_SECS);
+        if timeout == 0 {
+            invalid("timeout_secs", "must be at least 1");
+        }
+        let connect_timeout = entry
+            .connect_timeout_secs
+            .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS);
+        if connect_timeout == 0 {
+            invalid("connect_timeout_secs", "must be at least 1");
+        }
+        let requests_per_minute = entry
+            .requests_per_minute
+            .unwrap_or(DEFAULT_REQUESTS_PER_MINUTE);
+        if requests_per_minute == 0 {
+            invalid("requests_per_minute", "must be at least 1");
+        }
+        if let Some(token) = token {
+            workspaces.push(Workspace {
+                name: entry.name,
+                token,
+                timeout: Duration::from_secs(timeout),
+                connect_timeout: Duration::from_secs(connect_timeout),
+                requests_per_minute,
+            });
+        }
+    }
+    workspaces
+}
+
+fn validate_commands(
+    raw: RawCommands,
+    workspaces: &[Workspace],
+    errors: &mut Vec<ValidationError>,
+) -> Option<CommandSettings> {
+    let mut invalid = |field, message: String| {
+        errors.push(ValidationError {
+            entry: "[commands]".to_string(),
+            field,
+            message,
+        })
+    };
+    let mut source = |field, env, file| match token_source(env, file) {
+        Ok(source) => Some(source),
+        Err(message) => {
+            invalid(field, message.to_string());
+            None
+        }
+    };
+    let signing_secret = source(
+        "signing_secret_env",
+        raw.signing_secret_env,
+        raw.signing_secret_file,
+    );
+    let previous_signing_secret = source(
+        "previous_signing_secret_env",
+        raw.previous_signing_secret_env,
+        raw.previous_signing_secret_file,
+    );
+    let app_token = source("app_token_env", raw.app_token_env, raw.app_token_file);
+
+    let http = match raw.listen {
+        Some(listen) => {
+            let addr = match listen.parse::<SocketAddr>() {
+                Ok(addr) => Some(addr),
+                Err(e) => {
+                    invalid(
+                        "listen",
+                        format!("'{}' is not a socket address: {}", listen, e),
+                    );
+                    None
+                }
+            };
+            if signing_secret == Some(None) {
+                invalid(
+                    "signing_secret_env",
+                    "set signing_secret_env or signing_secret_file to verify HTTP requests"
+                        .to_string(),
+                );
+            }
+            let tolerance = raw
+                .signature_tolerance_secs
+                .unwrap_or(signature::DEFAULT_TOLERANCE.as_secs());
+            if tolerance == 0 {
+                invalid("signature_tolerance_secs", "must be at least 1".to_string());
+            }
+            match (addr, signing_secret.flatten(), previous_signing_secret) {
+                (Some(listen), Some(signing_secret), Some(previous_signing_secret))
+                    if tolerance > 0 =>
+                {
+                    Some(Some(HttpSettings {
+                        listen,
+                        signing_secret,
+                        previous_signing_secret,
+                        signature_tolerance: Duration::from_secs(tolerance),
+                    }))
+                }
+                _ => None,
+            }
+        }
+        None => Some(None),
+    };
+    if http == Some(None) && app_token == Some(None) {
+        invalid(
+            "listen",
+            "set listen (HTTP) and/or app_token_env (Socket Mode)".to_string(),
+        );
+    }
+    let workspace = match (raw.workspace, workspaces) {
+        (Some(workspace), _) if workspaces.iter().any(|w| w.name == workspace) => Some(workspace),
+        (Some(workspace), _) => {
+            invalid("workspace", format!("no workspace named '{}'", workspace));
+            None
+        }
+        (None, [only]) => Some(only.name.clone()),
+        (None, _) => {
+            invalid(
+                "workspace",
+                "required when more than one workspace is declared".to_string(),
+            );
+            None
+        }
+    };
+    let timezone = match raw.timezone.as_deref().map(Tz::from_str) {
+        None => Some(Tz::UTC),
+        Some(Ok(tz)) => Some(tz),
+        Some(Err(_)) => {
+            invalid(
+                "timezone",
+                format!(
+                    "unknown IANA time zone '{}'",
+                    raw.timezone.as_deref().unwrap_or_default()
+                ),
+            );
+            None
+        }
+    };
+    Some(CommandSettings {
+        http: http?,
+        app_token: app_token?,
+        workspace: workspace?,
+        timezone: timezone?,
+    })
+}
+
+/// A `<name>_env` / `<name>_file` pair; `Ok(None)` when neither is set.
+fn token_source(
+    env: Option<String>,
+    file: Option<PathBuf>,
+) -> Result<Option<TokenSource>, &'static str> {
+    match (env, file) {
+        (Some(var), None) if !var.trim().is_empty() => Ok(Some(TokenSource::Env(var))),
+        (Some(_), None) => Err("must not be empty"),
+        (None, Some(path)) => Ok(Some(TokenSource::File(path))),
+        (None, None) => Ok(None),
+        (Some(_), Some(_)) => Err("set either the _env or the _file variant, not both"),
+    }
+}
+
+fn validate_retry(raw: &RawRetry, errors: &mut Vec<ValidationError>) -> RetryPolicy {
+    let defaults = RetryPolicy::default();
+    let mut invalid = |field, message: &str| {
+        errors.push(ValidationError {
+            entry: "[retry]".to_string(),
+            field,
+            message: message.to_string(),
+        })
+    };
+
+    let max_attempts = raw.max_attempts.unwrap_or(defaults.max_attempts);
+    if max_attempts == 0 {
+        invalid("max_attempts", "must be at least 1");
+    }
+    let jitter = raw.jitter.unwrap_or(defaults.jitter);
+    if !(0.0..=1.0).contains(&jitter) {
+        invalid("jitter", "must be between 0.0 and 1.0");
+    }
+
+    let base_delay = raw
+        .base_delay_ms
+        .map(Duration::from_millis)
+        .unwrap_or(defaults.base_delay);
+    let max_delay = raw
+        .max_delay_ms
+        .map(Duration::from_millis)
+        .unwrap_or(defaults.max_delay);
+    if max_delay < base_delay {
+        invalid("max_delay_ms", "must not be less than base_delay_ms");
+    }
+    let deadline = raw
+        .deadline_secs
+        .map(Duration::from_secs)
+        .unwrap_or(defaults.deadline);
+    if deadline.is_zero() {
+        invalid("deadline_secs", "must be at least 1");
+    }
+
+    RetryPolicy {
+        max_attempts,
+        base_delay,
+        max_delay,
+        jitter,
+        deadline,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Validation errors for a config file, as "entry field: message".
+    fn errors(source: &str) -> Vec<String> {
+        let raw: RawConfig = toml::from_str(source).unwrap();
+        match validate(raw, None) {
+            Ok(_) => Vec::new(),
+            Err(ConfigError::Invalid(errors)) => errors
+                .iter()
+                .map(|e| format!("{} {}: {}", e.entry, e.field, e.message))
+                .collect(),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// A valid reminder named `name` plus the `extra` lines.
+    fn reminder(name: &str, extra: &str) -> String {
+        format!(
+            "[[reminder]]\nname = \"{}\"\nchannels = [\"C0123456789\"]\n{}\n",
+            name, extra
+        )
+    }
+
+    #[test]
+    fn accepts_a_valid_reminder() {
+        let source = reminder(
+            "standup",
+            "cron = \"0 0 9 * * Mon-Fri\"\ntext = \"Standup\"",
+        );
+        assert_eq!(errors(&source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn names_the_reminder_and_field() {
+        let valid = "cron = \"0 0 9 * * *\"\ntext = \"hi\"";
+        let duplicate = reminder("standup", valid) + &reminder("standup", valid);
+        assert_eq!(
+            errors(&duplicate),
+            ["reminder 'standup' name: duplicate reminder name"]
+        );
+
+        let bad = reminder(
+            "standup",
+            "cron = \"0 0 25 * * *\"\ntext = \"hi\"\ntimezone = \"Mars/Olympus\"",
+        );
+        assert_eq!(
+            errors(&bad),
+            [
+                "reminder 'standup' cron: invalid cron expression '0 0 25 * * *': Invalid expression: Invalid cron expression.",
+                "reminder 'standup' timezone: unknown IANA time zone 'Mars/Olympus'",
+            ]
+        );
+
+        let no_text = reminder("standup", "cron = \"0 0 9 * * *\"");
+        assert_eq!(
+            errors(&no_text),
+            ["reminder 'standup' text: must not be empty"]
+        );
+
+        let nameless = reminder("", valid);
+        assert_eq!(errors(&nameless), ["reminder #1 name: must not be empty"]);
+    }
+
+    #[test]
+    fn needs_exactly_one_schedule() {
+        let message =
+            "reminder 'standup' cron: set exactly one of cron, at, every, rrule or schedule";
+        assert_eq!(errors(&reminder("standup", "text = \"hi\"")), [message]);
+        let both = reminder(
+            "standup",
+            "cron = \"0 0 9 * * *\"\nat = \"2026-11-03 15:00\"\ntext = \"hi\"",
+        );
+        assert_eq!(errors(&both), [message]);
+    }
+
+    #[test]
+    fn bounds_durations() {
+        let valid = "cron = \"0 0 9 * * *\"\ntext = \"hi\"\nthread_under = \"standup\"";
+        let huge = i64::MAX;
+        let source = reminder(
+            "standup",
+            &format!(
+                "{}\ncatch_up_grace_minutes = {}\nthread_max_age_hours = {}\nfire_within_minutes = {}\n[reminder.ack]\nwithin_minutes = {}",
+                valid, huge, huge, huge, huge
+            ),
+        );
+        assert_eq!(
+            errors(&source),
+            [
+                "reminder 'standup' thread_max_age_hours: must not exceed 366 days",
+                "reminder 'standup' ack.within_minutes: must not exceed 366 days",
+                "reminder 'standup' fire_within_minutes: must not exceed 366 days",
+                "reminder 'standup' catch_up_grace_minutes: must not exceed 366 days",
+            ]
+        );
+        let zero = reminder("standup", &format!("{}\ncatch_up_grace_minutes = 0", valid));
+        assert_eq!(
+            errors(&zero),
+            ["reminder 'standup' catch_up_grace_minutes: must be positive"]
+        );
+
+        let retry = format!(
+            "[retry]\nbase_delay_ms = 5000\nmax_delay_ms = 100\ndeadline_secs = 0\n{}",
+            reminder("standup", valid)
+        );
+        assert_eq!(
+            errors(&retry),
+            [
+                "[retry] max_delay_ms: must not be less than base_delay_ms",
+                "[retry] deadline_secs: must be at least 1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::ack;
+use crate::buttons::{self, Action, Press};
+use crate::scheduler::Context;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Something that happened in Slack the bot acts on, received over HTTP or
+/// Socket Mode.
+#[derive(Debug)]
+pub enum Event {
+    /// A click on a reminder's buttons.
+    Press(Press),
+    /// An emoji reaction added to a message.
+    Reaction {
+        user: String,
+        reaction: String,
+        channel: String,
+        ts: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct EventCallback {
+    #[serde(rename = "type")]
+    kind: String,
+    event: ReactionAdded,
+}
+
+#[derive(Deserialize)]
+struct ReactionAdded {
+    #[serde(rename = "type")]
+    kind: String,
+    user: String,
+    reaction: String,
+    item: ReactedItem,
+}
+
+#[derive(Deserialize)]
+struct ReactedItem {
+    #[serde(rename = "type")]
+    kind: String,
+    channel: String,
+    ts: String,
+}
+
+/// The event in an interactive payload, if it is one we act on.
+pub fn from_interaction(payload: &Value) -> Option<Event> {
+    buttons::parse(payload).map(Event::Press)
+}
+
+/// The event in an Events API `event_callback`, if it is one we act on.
+pub fn from_callback(payload: &Value) -> Option<Event> {
+    let callback: EventCallback = serde_json::from_value(payload.clone()).ok()?;
+    let event = callback.event;
+    if callback.kind != "event_callback" || event.kind != "reaction_added" {
+        return None;
+    }
+    if event.item.kind != "message" {
+        return None;
+    }
+    Some(Event::Reaction {
+        user: event.user,
+        reaction: event.reaction,
+        channel: event.item.channel,
+        ts: event.item.ts,
+    })
+}
+
+pub async fn handle(event: Event, context: Arc<Context>, shutdown: CancellationToken) {
+    match event {
+        Event::Press(press) => {
+            if press.action == Action::Done {
+                ack::acknowledge(&context, &press.channel, &press.ts, &press.user, None).await;
+            }
+            buttons::press(press, context, shutdown).await;
+        }
+        Event::Reaction {
+            user,
+            reaction,
+            channel,
+            ts,
+        } => ack::acknowledge(&context, &channel, &ts, &user, Some(&reaction)).await,
+    }
+}
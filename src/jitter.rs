@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// How far past its scheduled time a reminder posts, to spread reminders
+/// that share a schedule. Delays are derived from the reminder's name, so
+/// they are the same on every run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jitter {
+    /// The same delay for every occurrence, up to this much.
+    Fixed(Duration),
+    /// A different delay for each occurrence, within this window.
+    Window(Duration),
+}
+
+impl Jitter {
+    /// Delay for the occurrence of reminder `name` scheduled at `scheduled`.
+    pub fn delay(&self, name: &str, scheduled: DateTime<Utc>) -> Duration {
+        let (seed, span) = match self {
+            Jitter::Fixed(max) => (fnv1a(name.as_bytes()), max.as_secs() + 1),
+            Jitter::Window(window) => {
+                let key = format!("{}@{}", name, scheduled.timestamp());
+                (fnv1a(key.as_bytes()), window.as_secs())
+            }
+        };
+        Duration::from_secs(seed % span.max(1))
+    }
+}
+
+/// 64-bit FNV-1a: unlike `DefaultHasher`, guaranteed not to change between
+/// Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn delays_are_stable_and_bounded() {
+        let at = |hour| Utc.with_ymd_and_hms(2026, 10, 15, hour, 0, 0).unwrap();
+        let fixed = Jitter::Fixed(Duration::from_secs(120));
+        assert_eq!(
+            fixed.delay("standup", at(9)),
+            fixed.delay("standup", at(10))
+        );
+        assert!(fixed.delay("standup", at(9)) <= Duration::from_secs(120));
+
+        let window = Jitter::Window(Duration::from_secs(600));
+        let delays: Vec<Duration> = (0..24)
+            .map(|hour| window.delay("standup", at(hour)))
+            .collect();
+        assert!(delays.iter().all(|delay| *delay < Duration::from_secs(600)));
+        assert!(delays.windows(2).any(|pair| pair[0] != pair[1]));
+        assert_eq!(delays[3], window.delay("standup", at(3)));
+
+        // Different reminders on the same schedule are spread out
+        let names = ["standup", "retro", "deploy", "oncall", "lunch"];
+        let spread: Vec<Duration> = names.iter().map(|name| fixed.delay(name, at(9))).collect();
+        assert!(spread.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+}
@@ -1,143 +1,447 @@
-use chrono::Utc;
-use cron::Schedule;
+mod ack;
+mod blocks;
+mod buttons;
+mod calendar;
+mod commands;
+mod config;
+mod events;
+mod jitter;
+mod names;
+mod phrase;
+mod ratelimit;
+mod retry;
+mod rrule;
+mod schedule;
+mod scheduler;
+mod server;
+mod signals;
+mod signature;
+mod slack;
+mod socket;
+mod state;
+mod template;
+
+use chrono_tz::Tz;
+use config::{Config, Reminder, Workspace};
 use env_logger::Env;
+use names::NameCache;
+use ratelimit::RateLimiter;
 use reqwest::Client;
-use serde::Serialize;
+use scheduler::{Context, Scheduler};
+use signals::{SignalEvent, Signals};
+use signature::Verifier;
+use slack::SlackClient;
+use state::StateStore;
+use std::collections::HashMap;
 use std::env;
+use std::fs;
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::time::{sleep_until, Duration, Instant};
-
-#[derive(Serialize)]
-struct SlackMessage<'a> {
-    channel: &'a str,
-    text: &'a str,
-}
+use std::time::SystemTime;
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging with environment variable support
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
 
-    // Retrieve Slack credentials from environment
-    let slack_bot_token = env::var("SLACK_BOT_TOKEN")
-        .expect("SLACK_BOT_TOKEN must be set in the environment");
-    let slack_channel_id = env::var("SLACK_CHANNEL_ID")
-        .expect("SLACK_CHANNEL_ID must be set in the environment");
-
-    // Initialize HTTP client
-    let client = Arc::new(Client::new());
-
-    // Define the reminder message
-    let reminder_text = "This is your scheduled reminder!";
-
-    // Define cron expressions for every Sunday at 2 PM UTC and 10 PM UTC
-    // Option 1: Using numerical representation for Sunday as 7
-    let cron_expr_2pm = "0 0 14 * * SUN"; // At 14:00:00 on Sunday
-    let cron_expr_10pm = "0 0 22 * * SUN"; // At 22:00:00 on Sunday
-
-    // **Debug Prints (Optional)**
-    println!("Parsing cron expression for 2 PM: '{}'", cron_expr_2pm);
-    println!("Parsing cron expression for 10 PM: '{}'", cron_expr_10pm);
-
-    // Parse cron expressions with detailed error messages
-    let schedule_2pm = Schedule::from_str(cron_expr_2pm)
-        .unwrap_or_else(|e| panic!("Invalid cron expression for 2 PM: {}", e));
-    let schedule_10pm = Schedule::from_str(cron_expr_10pm)
-        .unwrap_or_else(|e| panic!("Invalid cron expression for 10 PM: {}", e));
-
-    // Clone necessary variables for tasks
-    let client_clone_2pm = Arc::clone(&client);
-    let client_clone_10pm = Arc::clone(&client);
-    let token_2pm = slack_bot_token.clone();
-    let token_10pm = slack_bot_token.clone();
-    let channel_2pm = slack_channel_id.clone();
-    let channel_10pm = slack_channel_id.clone();
-    let text_2pm = reminder_text.to_string();
-    let text_10pm = reminder_text.to_string();
-
-    // Spawn task for 2 PM reminders
-    tokio::spawn(async move {
-        run_schedule(
-            schedule_2pm,
-            client_clone_2pm,
-            &token_2pm,
-            &channel_2pm,
-            &text_2pm,
-        )
-            .await;
-    });
+    // `schedule "<schedule>" [timezone]` explains a schedule and exits
+    let args = config::positional_args(env::args().skip(1));
+    if args.first().map(String::as_str) == Some("schedule") {
+        let ok = explain_schedule(
+            args.get(1).map(String::as_str),
+            args.get(2).map(String::as_str),
+        );
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    // Fallback channel for reminders that do not set their own
+    let slack_channel_id = env::var("SLACK_CHANNEL_ID").ok();
+
+    // Load reminders from the config file (--config, REMINDER_CONFIG or ./reminders.toml)
+    let config_path = config::config_path_from_args(env::args().skip(1));
+    println!("Loading reminders from '{}'", config_path.display());
+    let mut config = match config::load(&config_path, slack_channel_id.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Open the state store recording fires and the delivery ledger
+    let state = match StateStore::open(&config.settings.state_path) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // `ledger [reminder]` prints the delivery ledger and exits
+    if args.first().map(String::as_str) == Some("ledger") {
+        print_ledger(&state, args.get(1).map(String::as_str));
+        return Ok(());
+    }
+
+    if config.reminders.is_empty() {
+        eprintln!("No reminders configured in '{}'", config_path.display());
+    }
+
+    // One client per workspace, each with its own token and rate limit
+    let clients = match connect(&config.settings.workspaces) {
+        Ok(clients) => clients,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Turn #channel and @email references into IDs
+    let mut names = NameCache::open(&config.settings.names_cache_path);
+    if let Err(e) = names::resolve(
+        &mut config.reminders,
+        &clients,
+        &config.settings.retry,
+        &mut names,
+    )
+    .await
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 
-    // Spawn task for 10 PM reminders
-    tokio::spawn(async move {
-        run_schedule(
-            schedule_10pm,
-            client_clone_10pm,
-            &token_10pm,
-            &channel_10pm,
-            &text_10pm,
-        )
-            .await;
+    // Shared by every reminder task
+    let shutdown = CancellationToken::new();
+    let context = Arc::new(Context {
+        clients,
+        retry: config.settings.retry.clone(),
+        state,
+        shutdown: shutdown.clone(),
     });
 
-    println!("Slack Reminder Bot is running...");
+    // Spawn one task per configured reminder and per reminder added with
+    // /remind-bot, skipping paused ones
+    let mut configured = config.reminders;
+    let mut scheduler = Scheduler::new(Arc::clone(&context), shutdown.clone());
+    for reminder in &configured {
+        if let Some(reason) = scheduler::ended(reminder, &context.state) {
+            println!(
+                "Reminder '{}' has ended ({}); not scheduling it",
+                reminder.name, reason
+            );
+        }
+    }
+    for reminder in commands::active(&configured, scheduler.context()) {
+        scheduler.start(reminder, true);
+    }
+    let names = Arc::new(Mutex::new(names));
 
-    // Keep the program running indefinitely
-    loop {
-        tokio::time::sleep(Duration::from_secs(60)).await;
+    // Snoozed posts and escalations from before a restart
+    buttons::resume(&context, &shutdown);
+    ack::resume(&context);
+
+    // Serve the /remind-bot slash command, button presses and reactions
+    // over HTTP and/or Socket Mode; all are handled below
+    let (command_tx, mut command_rx) = mpsc::channel(16);
+    let (event_tx, mut event_rx) = mpsc::channel(16);
+    let (change_tx, mut change_rx) = mpsc::channel(16);
+    if let Some(http) = config
+        .settings
+        .commands
+        .as_ref()
+        .and_then(|c| c.http.as_ref())
+    {
+        let secrets = http.signing_secret.load().and_then(|current| {
+            let previous = http
+                .previous_signing_secret
+                .as_ref()
+                .map(|source| source.load())
+                .transpose()?;
+            Ok((current, previous))
+        });
+        let verifier = match secrets {
+            Ok((current, previous)) => Verifier::new(current)
+                .with_previous(previous)
+                .with_tolerance(http.signature_tolerance),
+            Err(e) => {
+                eprintln!("[commands]: {}", e);
+                std::process::exit(1);
+            }
+        };
+        if let Err(e) = server::spawn(
+            http.listen,
+            verifier,
+            command_tx.clone(),
+            event_tx.clone(),
+            shutdown.clone(),
+        ) {
+            eprintln!("Failed to listen on {}: {}", http.listen, e);
+            std::process::exit(1);
+        }
+    }
+    if let Some(app_token) = config
+        .settings
+        .commands
+        .as_ref()
+        .and_then(|c| c.app_token.as_ref())
+    {
+        let client = match app_token.load() {
+            // apps.connections.open is only called on (re)connect
+            Ok(token) => Arc::new(SlackClient::new(
+                Client::new(),
+                token,
+                RateLimiter::per_minute(10),
+            )),
+            Err(e) => {
+                eprintln!("[commands]: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let open = move || {
+            let client = Arc::clone(&client);
+            async move { client.open_connection().await.map_err(|e| e.to_string()) }
+        };
+        tokio::spawn(socket::run(open, command_tx, event_tx, shutdown.clone()));
+    } else {
+        drop(command_tx);
+        drop(event_tx);
     }
-}
 
-async fn run_schedule(
-    schedule: Schedule,
-    client: Arc<Client>,
-    token: &str,
-    channel: &str,
-    text: &str,
-) {
-    let mut upcoming = schedule.upcoming(Utc);
+    let mut signals = Signals::install()?;
+
+    // Poll the config file's modification time to pick up edits. A zero
+    // interval disables the branch below; interval() itself rejects zero.
+    let watch_enabled = !config.settings.watch_interval.is_zero();
+    let mut watch = tokio::time::interval(
+        config
+            .settings
+            .watch_interval
+            .max(std::time::Duration::from_secs(1)),
+    );
+    let mut last_modified = modified_time(&config_path);
+
+    println!("Slack Reminder Bot is running...");
+
+    // Run until SIGTERM/SIGINT, reloading on SIGHUP or config edits and
+    // reporting tasks that end on their own
+    let mut exit_code = EXIT_OK;
     loop {
-        if let Some(datetime) = upcoming.next() {
-            let now = Utc::now();
-            let duration = datetime - now;
-            let duration_std = match duration.to_std() {
-                Ok(d) => d,
-                Err(_) => {
-                    eprintln!("Scheduled time is in the past. Skipping.");
-                    continue;
+        tokio::select! {
+            event = signals.recv() => match event {
+                SignalEvent::Shutdown(signal) => {
+                    println!("Received {}, shutting down...", signal);
+                    break;
+                }
+                SignalEvent::Reload => {
+                    println!("Received SIGHUP, reloading '{}'", config_path.display());
+                    last_modified = modified_time(&config_path);
+                    reload(&config_path, slack_channel_id.as_deref(), &config.settings, &mut scheduler, &mut configured, &names).await;
+                }
+            },
+            _ = watch.tick(), if watch_enabled => {
+                let modified = modified_time(&config_path);
+                if modified != last_modified {
+                    last_modified = modified;
+                    println!("'{}' changed, reloading", config_path.display());
+                    reload(&config_path, slack_channel_id.as_deref(), &config.settings, &mut scheduler, &mut configured, &names).await;
                 }
-            };
-            let instant = Instant::now() + duration_std;
-
-            println!("Next reminder scheduled at {}", datetime);
-
-            sleep_until(instant).await;
-
-            // Send the Slack message
-            let message = SlackMessage { channel, text };
-
-            match client
-                .post("https://slack.com/api/chat.postMessage")
-                .bearer_auth(token)
-                .json(&message)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        println!("Message sent successfully at {}", Utc::now());
-                    } else {
-                        let error_text = response.text().await.unwrap_or_default();
-                        eprintln!("Failed to send message: {}", error_text);
-                    }
+            }
+            Some(clean) = scheduler.join_next() => {
+                if !clean {
+                    exit_code = EXIT_TASK_PANICKED;
                 }
-                Err(e) => {
-                    eprintln!("Error sending message: {:?}", e);
+            }
+            Some(event) = event_rx.recv() => {
+                tokio::spawn(events::handle(event, Arc::clone(&context), shutdown.clone()));
+            }
+            // Commands may wait on Slack to resolve names, so they run on
+            // their own task and only hand scheduler changes back here
+            Some(request) = command_rx.recv() => {
+                if let Some(settings) = config.settings.commands.clone() {
+                    let context = Arc::clone(&context);
+                    let configured = configured.clone();
+                    let names = Arc::clone(&names);
+                    let changes = change_tx.clone();
+                    tokio::spawn(async move {
+                        let reply = commands::execute(request.invocation, &context, &configured, &settings, &names, &changes).await;
+                        let _ = request.reply.send(reply);
+                    });
                 }
             }
-        } else {
-            eprintln!("No upcoming schedule found. Exiting task.");
-            break;
+            Some(()) = change_rx.recv() => {
+                commands::apply(&mut scheduler, &configured);
+            }
+        }
+    }
+
+    // Stop scheduling new occurrences and let in-flight sends finish
+    match scheduler.shutdown(config.settings.shutdown_timeout).await {
+        Ok(true) => {}
+        Ok(false) => exit_code = EXIT_TASK_PANICKED,
+        Err(aborted) => {
+            eprintln!(
+                "{} reminder task(s) still running after {:?}, aborted",
+                aborted, config.settings.shutdown_timeout
+            );
+            exit_code = EXIT_SHUTDOWN_TIMEOUT;
+        }
+    }
+
+    if let Err(e) = scheduler.context().state.flush().await {
+        eprintln!("Failed to flush state: {}", e);
+        exit_code = EXIT_STATE_FLUSH_FAILED;
+    }
+
+    println!("Slack Reminder Bot stopped (exit code {})", exit_code);
+    std::process::exit(exit_code);
+}
+
+/// Validate the config file again and apply the reminder changes, keeping
+/// the ones added with /remind-bot. An invalid file is rejected as a whole
+/// and the running reminders are left untouched.
+async fn reload(
+    path: &Path,
+    default_channel: Option<&str>,
+    settings: &config::Settings,
+    scheduler: &mut Scheduler,
+    configured: &mut Vec<Reminder>,
+    names: &Mutex<NameCache>,
+) {
+    let Config {
+        settings: new_settings,
+        mut reminders,
+    } = match config::load(path, default_channel) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Reload rejected, keeping the current reminders: {}", e);
+            return;
+        }
+    };
+    let context = scheduler.context();
+    // Clients are only built at startup
+    if let Some(reminder) = reminders
+        .iter()
+        .find(|reminder| !context.clients.contains_key(&reminder.workspace))
+    {
+        eprintln!(
+            "Reload rejected, keeping the current reminders: reminder '{}' uses workspace '{}', which needs a restart",
+            reminder.name, reminder.workspace
+        );
+        return;
+    }
+    let resolved = names::resolve(
+        &mut reminders,
+        &context.clients,
+        &context.retry,
+        &mut *names.lock().await,
+    )
+    .await;
+    if let Err(e) = resolved {
+        eprintln!("Reload rejected, keeping the current reminders: {}", e);
+        return;
+    }
+    if &new_settings != settings {
+        eprintln!("Changes outside [[reminder]] entries take effect after a restart");
+    }
+
+    *configured = reminders;
+    let summary = commands::apply(scheduler, configured);
+    println!(
+        "Reload applied: {} added, {} removed, {} rescheduled, {} unchanged",
+        summary.added.len(),
+        summary.removed.len(),
+        summary.rescheduled.len(),
+        summary.unchanged
+    );
+}
+
+/// Build the HTTP client, rate limiter and token for every workspace.
+fn connect(workspaces: &[Workspace]) -> Result<HashMap<String, SlackClient>, String> {
+    let mut clients = HashMap::new();
+    for workspace in workspaces {
+        let token = workspace
+            .token
+            .load()
+            .map_err(|e| format!("workspace '{}': {}", workspace.name, e))?;
+        let http = Client::builder()
+            .timeout(workspace.timeout)
+            .connect_timeout(workspace.connect_timeout)
+            .build()
+            .map_err(|e| format!("workspace '{}': {}", workspace.name, e))?;
+        let limiter = RateLimiter::per_minute(workspace.requests_per_minute);
+        clients.insert(
+            workspace.name.clone(),
+            SlackClient::new(http, token, limiter),
+        );
+    }
+    Ok(clients)
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Process exit codes.
+const EXIT_OK: i32 = 0;
+const EXIT_TASK_PANICKED: i32 = 2;
+const EXIT_SHUTDOWN_TIMEOUT: i32 = 3;
+const EXIT_STATE_FLUSH_FAILED: i32 = 4;
+
+/// Print how a schedule is understood and when it fires next, to check a
+/// phrase before putting it in the config file.
+fn explain_schedule(text: Option<&str>, timezone: Option<&str>) -> bool {
+    let Some(text) = text else {
+        eprintln!("Usage: schedule \"<schedule>\" [timezone]");
+        return false;
+    };
+    let tz = match timezone.map(Tz::from_str).transpose() {
+        Ok(tz) => tz.unwrap_or(Tz::UTC),
+        Err(e) => {
+            eprintln!("Unknown timezone: {}", e);
+            return false;
+        }
+    };
+    match schedule::interpret(text) {
+        Ok((schedule, meaning)) => {
+            println!("'{}' means {} ({})", text, meaning, tz);
+            for occurrence in schedule::upcoming(&schedule, tz, chrono::Utc::now(), 5) {
+                println!("  {}", occurrence.local.format("%a %Y-%m-%d %H:%M %Z"));
+            }
+            true
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            false
         }
     }
-}
\ No newline at end of file
+}
+
+fn print_ledger(state: &StateStore, reminder: Option<&str>) {
+    let deliveries = state.deliveries(reminder);
+    if deliveries.is_empty() {
+        println!("No deliveries recorded");
+    }
+    for (name, delivery) in deliveries {
+        println!(
+            "{}\t{}\t{}\t{:?}\tattempted {}\tchannel {}\tts {}{}",
+            name,
+            delivery.scheduled,
+            if delivery.destination.is_empty() {
+                "-"
+            } else {
+                &delivery.destination
+            },
+            delivery.status,
+            delivery.attempted_at,
+            delivery.channel.as_deref().unwrap_or("-"),
+            delivery.ts.as_deref().unwrap_or("-"),
+            delivery
+                .error
+                .map(|error| format!("\terror {}", error))
+                .unwrap_or_default()
+        );
+    }
+}
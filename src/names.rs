@@ -0,0 +1,445 @@
+use crate::config::{ConfigError, Destination, Escalation, Reminder, ValidationError};
+use crate::retry::RetryPolicy;
+use crate::slack::{SlackClient, SlackError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default location of the name cache when the config does not set one.
+pub const DEFAULT_NAMES_CACHE_PATH: &str = "slack-names.json";
+
+/// Check the shape of a `#channel-name` or `@email` reference at load time;
+/// plain IDs are passed through.
+pub fn check_reference(destination: &Destination) -> Result<(), String> {
+    match destination {
+        Destination::Channel(id) => match id.strip_prefix('#') {
+            Some(name) if name.is_empty() || name.contains(char::is_whitespace) => {
+                Err(format!("'{}' is not a valid channel name", id))
+            }
+            _ => Ok(()),
+        },
+        Destination::User(id) => match id.strip_prefix('@') {
+            Some(email) if !email.contains('@') => Err(format!(
+                "'{}' must be an email address, e.g. @name@example.com",
+                id
+            )),
+            _ => Ok(()),
+        },
+        Destination::UserGroup(_) => Ok(()),
+    }
+}
+
+/// Resolved names as stored on disk.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Names {
+    /// Channel name (without `#`) to ID.
+    #[serde(default)]
+    channels: BTreeMap<String, String>,
+    /// Email to user ID.
+    #[serde(default)]
+    users: BTreeMap<String, String>,
+}
+
+/// Resolved names of every workspace, by workspace name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    #[serde(default)]
+    workspaces: BTreeMap<String, Names>,
+}
+
+/// On-disk mapping of `#channel` and `@email` references to Slack IDs, so
+/// restarts and reloads only ask Slack about names they have not seen.
+pub struct NameCache {
+    path: PathBuf,
+    file: CacheFile,
+}
+
+impl NameCache {
+    /// A missing or unreadable cache starts out empty; it is only a cache.
+    pub fn open(path: &Path) -> Self {
+        let file = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Ignoring unreadable name cache {}: {}", path.display(), e);
+                CacheFile::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => CacheFile::default(),
+            Err(e) => {
+                eprintln!("Ignoring unreadable name cache {}: {}", path.display(), e);
+                CacheFile::default()
+            }
+        };
+        NameCache {
+            path: path.to_path_buf(),
+            file,
+        }
+    }
+
+    fn save(&self) {
+        let result = serde_json::to_string_pretty(&self.file)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                let tmp = self.path.with_extension("json.tmp");
+                fs::write(&tmp, json).map_err(|e| e.to_string())?;
+                fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            eprintln!("Failed to write name cache {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Replace every `#channel-name` and `@email` destination (and escalation
+/// DM) with its Slack ID, asking the reminder's workspace about names missing
+/// from the cache. Names that do not exist, and channels the bot is not a
+/// member of, are reported like any other validation error.
+pub async fn resolve(
+    reminders: &mut [Reminder],
+    clients: &HashMap<String, SlackClient>,
+    retry: &RetryPolicy,
+    cache: &mut NameCache,
+) -> Result<(), ConfigError> {
+    let mut errors = Vec::new();
+    let mut changed = false;
+    let mut workspaces: Vec<&String> = clients.keys().collect();
+    workspaces.sort();
+    for workspace in workspaces {
+        let mut group: Vec<&mut Reminder> = reminders
+            .iter_mut()
+            .filter(|reminder| &reminder.workspace == workspace)
+            .collect();
+        if group.is_empty() {
+            continue;
+        }
+        let names = cache.file.workspaces.entry(workspace.clone()).or_default();
+        changed |=
+            resolve_workspace(&mut group, &clients[workspace], retry, names, &mut errors).await;
+    }
+
+    if changed {
+        cache.save();
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid(errors))
+    }
+}
+
+/// Resolve the references of reminders in one workspace. Returns whether
+/// `names` gained entries.
+async fn resolve_workspace(
+    reminders: &mut [&mut Reminder],
+    client: &SlackClient,
+    retry: &RetryPolicy,
+    names: &mut Names,
+    errors: &mut Vec<ValidationError>,
+) -> bool {
+    let mut channel_names = HashSet::new();
+    let mut emails = HashSet::new();
+    for destination in reminders.iter().flat_map(|r| &r.destinations) {
+        match destination {
+            Destination::Channel(id) => {
+                if let Some(name) = id.strip_prefix('#') {
+                    if !names.channels.contains_key(name) {
+                        channel_names.insert(name.to_string());
+                    }
+                }
+            }
+            Destination::User(id) => {
+                if let Some(email) = id.strip_prefix('@') {
+                    if !names.users.contains_key(email) {
+                        emails.insert(email.to_string());
+                    }
+                }
+            }
+            Destination::UserGroup(_) => {}
+        }
+    }
+    for step in reminders
+        .iter()
+        .filter_map(|r| r.ack.as_ref())
+        .flat_map(|ack| &ack.escalate)
+    {
+        if let Escalation::Dm(user) = step {
+            if let Some(email) = user.strip_prefix('@') {
+                if !names.users.contains_key(email) {
+                    emails.insert(email.to_string());
+                }
+            }
+        }
+    }
+
+    // Why each name that is still unresolved failed
+    let mut problems: HashMap<String, String> = HashMap::new();
+    let mut changed = false;
+
+    if !channel_names.is_empty() {
+        match list_channels(client, retry).await {
+            Ok(channels) => {
+                for name in channel_names {
+                    match channels.get(&name) {
+                        Some((id, true)) => {
+                            println!("Resolved #{} to {}", name, id);
+                            names.channels.insert(name, id.clone());
+                            changed = true;
+                        }
+                        Some((id, false)) => {
+                            let message = format!("the bot is not a member of #{} ({})", name, id);
+                            problems.insert(format!("#{}", name), message);
+                        }
+                        None => {
+                            let message = format!("no channel named #{} (or it is archived)", name);
+                            problems.insert(format!("#{}", name), message);
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                for name in channel_names {
+                    let message = format!("could not look up #{}: {}", name, e);
+                    problems.insert(format!("#{}", name), message);
+                }
+            }
+        }
+    }
+
+    for email in emails {
+        let label = format!("users.lookupByEmail {}", email);
+        match retry
+            .run(&label, || client.lookup_user_by_email(&email))
+            .await
+        {
+            Ok(id) => {
+                println!("Resolved @{} to {}", email, id);
+                names.users.insert(email, id);
+                changed = true;
+            }
+            Err(SlackError::Unknown(code)) if code == "users_not_found" => {
+                let message = format!("no user with email {}", email);
+                problems.insert(format!("@{}", email), message);
+            }
+            Err(e) => {
+                let message = format!("could not look up @{}: {}", email, e);
+                problems.insert(format!("@{}", email), message);
+            }
+        }
+    }
+
+    for reminder in reminders.iter_mut() {
+        let mut resolved = Vec::new();
+        for destination in reminder.destinations.drain(..) {
+            let (field, reference, target) = match &destination {
+                Destination::Channel(id) => match id.strip_prefix('#') {
+                    Some(name) => (
+                        "channels",
+                        id,
+                        names.channels.get(name).cloned().map(Destination::Channel),
+                    ),
+                    None => ("channels", id, Some(destination.clone())),
+                },
+                Destination::User(id) => match id.strip_prefix('@') {
+                    Some(email) => (
+                        "users",
+                        id,
+                        names.users.get(email).cloned().map(Destination::User),
+                    ),
+                    None => ("users", id, Some(destination.clone())),
+                },
+                Destination::UserGroup(id) => ("usergroups", id, Some(destination.clone())),
+            };
+            let Some(target) = target else {
+                errors.push(ValidationError {
+                    entry: format!("reminder '{}'", reminder.name),
+                    field,
+                    message: problems
+                        .get(reference)
+                        .cloned()
+                        .unwrap_or_else(|| format!("could not resolve {}", reference)),
+                });
+                continue;
+            };
+            if resolved.contains(&target) {
+                errors.push(ValidationError {
+                    entry: format!("reminder '{}'", reminder.name),
+                    field,
+                    message: format!("{} is listed twice once names are resolved", target),
+                });
+            } else {
+                resolved.push(target);
+            }
+        }
+        reminder.destinations = resolved;
+
+        let Some(ack) = &mut reminder.ack else {
+            continue;
+        };
+        for step in &mut ack.escalate {
+            let Escalation::Dm(user) = step else {
+                continue;
+            };
+            let Some(email) = user.strip_prefix('@') else {
+                continue;
+            };
+            match names.users.get(email) {
+                Some(id) => *user = id.clone(),
+                None => errors.push(ValidationError {
+                    entry: format!("reminder '{}'", reminder.name),
+                    field: "ack.escalate",
+                    message: problems
+                        .get(user.as_str())
+                        .cloned()
+                        .unwrap_or_else(|| format!("could not resolve {}", user)),
+                }),
+            }
+        }
+    }
+    changed
+}
+
+/// Every channel visible to the bot, by name, with whether it is a member.
+async fn list_channels(
+    client: &SlackClient,
+    retry: &RetryPolicy,
+) -> Result<HashMap<String, (String, bool)>, SlackError> {
+    let mut channels = HashMap::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = retry
+            .run("conversations.list", || {
+                client.list_channels(cursor.as_deref())
+            })
+            .await?;
+        for channel in page.channels {
+            channels.insert(channel.name, (channel.id, channel.is_member));
+        }
+        match page.response_metadata {
+            Some(meta) if !meta.next_cursor.is_empty() => cursor = Some(meta.next_cursor),
+            _ => return Ok(channels),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::ratelimit::RateLimiter;
+
+    #[test]
+    fn checks_the_shape_of_references() {
+        let channel = |id: &str| check_reference(&Destination::Channel(id.to_string()));
+        let user = |id: &str| check_reference(&Destination::User(id.to_string()));
+        assert_eq!(channel("C0123456789"), Ok(()));
+        assert_eq!(channel("#ops-alerts"), Ok(()));
+        assert_eq!(
+            channel("#"),
+            Err("'#' is not a valid channel name".to_string())
+        );
+        assert_eq!(
+            channel("#ops alerts"),
+            Err("'#ops alerts' is not a valid channel name".to_string())
+        );
+        assert_eq!(user("U0123456789"), Ok(()));
+        assert_eq!(user("@lead@example.com"), Ok(()));
+        assert_eq!(
+            user("@lead"),
+            Err("'@lead' must be an email address, e.g. @name@example.com".to_string())
+        );
+    }
+
+    /// Resolve the reminders of `source` against the cached `names` only;
+    /// nothing here needs to ask Slack.
+    async fn resolve_cached(
+        test: &str,
+        source: &str,
+        names: &mut Names,
+    ) -> (Vec<Reminder>, Vec<String>) {
+        let path = std::env::temp_dir().join(format!("{}-{}.toml", test, std::process::id()));
+        fs::write(&path, source).unwrap();
+        let mut reminders = config::load(&path, None).unwrap().reminders;
+        fs::remove_file(&path).unwrap();
+        let client = SlackClient::new(
+            reqwest::Client::new(),
+            "xoxb-test".to_string(),
+            RateLimiter::per_minute(60),
+        );
+        let mut errors = Vec::new();
+        let mut group: Vec<&mut Reminder> = reminders.iter_mut().collect();
+        let changed = resolve_workspace(
+            &mut group,
+            &client,
+            &RetryPolicy::default(),
+            names,
+            &mut errors,
+        )
+        .await;
+        assert!(!changed);
+        let errors = errors
+            .iter()
+            .map(|e| format!("{} {}: {}", e.entry, e.field, e.message))
+            .collect();
+        (reminders, errors)
+    }
+
+    fn cached() -> Names {
+        Names {
+            channels: BTreeMap::from([("general".to_string(), "C0123456789".to_string())]),
+            users: BTreeMap::from([("lead@example.com".to_string(), "U0123456789".to_string())]),
+        }
+    }
+
+    #[tokio::test]
+    async fn replaces_names_with_cached_ids() {
+        let source = r##"
+[[reminder]]
+name = "standup"
+cron = "0 0 9 * * *"
+text = "Standup"
+channels = ["#general", "C0987654321"]
+users = ["@lead@example.com"]
+
+[reminder.ack]
+within_minutes = 60
+escalate = ["repost", { dm = "@lead@example.com" }]
+"##;
+        let (reminders, errors) = resolve_cached("names-ids", source, &mut cached()).await;
+        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(
+            reminders[0].destinations,
+            [
+                Destination::Channel("C0123456789".to_string()),
+                Destination::Channel("C0987654321".to_string()),
+                Destination::User("U0123456789".to_string()),
+            ]
+        );
+        assert_eq!(
+            reminders[0].ack.as_ref().unwrap().escalate,
+            [
+                Escalation::Repost,
+                Escalation::Dm("U0123456789".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_destinations_that_resolve_to_the_same_id() {
+        let source = r##"
+[[reminder]]
+name = "standup"
+cron = "0 0 9 * * *"
+text = "Standup"
+channels = ["#general", "C0123456789"]
+users = ["@lead@example.com", "U0123456789"]
+"##;
+        let (reminders, errors) = resolve_cached("names-twice", source, &mut cached()).await;
+        assert_eq!(
+            errors,
+            [
+                "reminder 'standup' channels: channel C0123456789 is listed twice once names are resolved",
+                "reminder 'standup' users: user U0123456789 is listed twice once names are resolved",
+            ]
+        );
+        assert_eq!(reminders[0].destinations.len(), 2);
+    }
+}
@@ -0,0 +1,509 @@
+use crate::rrule::{ByDay, Freq, Rule, MAX_INTERVAL};
+use crate::schedule::Schedule;
+use chrono::{NaiveDate, NaiveTime, Timelike, Weekday};
+use std::str::FromStr;
+
+const WEEK: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+const ORDINALS: [&str; 5] = ["first", "second", "third", "fourth", "fifth"];
+
+/// A schedule written as a phrase, and how it was understood.
+#[derive(Debug)]
+pub struct Phrase {
+    pub schedule: Schedule,
+    /// The interpretation in plain words, to echo back to whoever wrote it.
+    pub meaning: String,
+}
+
+/// Parse phrases such as "every weekday at 9:30", "first Monday of the
+/// month at 10am", "every 2 weeks on Friday at 4pm", "last business day of
+/// the month at 5pm" or "the 15th of every month at noon". Word order is
+/// loose; every phrase needs a time.
+pub fn parse(text: &str) -> Result<Phrase, String> {
+    let mut lower = format!(" {} ", text.to_lowercase().replace(',', " "));
+    for business in ["business", "working", "work"] {
+        for suffix in ["s", ""] {
+            lower = lower.replace(&format!(" {} day{} ", business, suffix), " weekday ");
+        }
+    }
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    let mut time = None;
+    let mut start = None;
+    let mut days: Vec<Weekday> = Vec::new();
+    let mut every_day = false;
+    let mut monthly = false;
+    let mut weeks = None;
+    // A bare number, counting the weeks in "every 2 weeks"
+    let mut count = None;
+    let mut ordinal = None;
+
+    let mut set_time = |found: NaiveTime| match time.replace(found) {
+        Some(_) => Err("give a single time, e.g. 'at 9:30'".to_string()),
+        None => Ok(()),
+    };
+    let mut i = 0;
+    while let Some(&word) = words.get(i) {
+        i += 1;
+        match word {
+            "every" | "each" | "on" | "the" | "of" | "and" | "in" => {}
+            "at" if matches!(words.get(i), Some(&("noon" | "midday" | "midnight"))) => {}
+            "at" => {
+                let (found, used) = words
+                    .get(i)
+                    .and_then(|&word| parse_time(word, words.get(i + 1).copied(), true))
+                    .ok_or_else(|| format!("expected a time after 'at' in '{}'", text.trim()))?;
+                set_time(found)?;
+                i += 1 + used;
+            }
+            "starting" | "from" | "beginning" => {
+                let date = words.get(i).copied().unwrap_or_default();
+                let found = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map_err(|_| format!("expected a date like 2026-11-02 after '{}'", word))?;
+                start = Some(found);
+                i += 1;
+            }
+            "day" | "days" | "daily" => every_day = true,
+            "weekday" | "weekdays" => days.extend(&WEEK[..5]),
+            "weekend" | "weekends" => days.extend(&WEEK[5..]),
+            "week" | "weeks" | "weekly" => weeks = Some(count.take().unwrap_or(1)),
+            "other" => count = Some(2),
+            "biweekly" | "fortnightly" | "fortnight" => weeks = Some(2),
+            "month" | "months" | "monthly" => monthly = true,
+            "noon" | "midday" => set_time(NaiveTime::MIN + chrono::Duration::hours(12))?,
+            "midnight" => set_time(NaiveTime::MIN)?,
+            _ => {
+                if let Some(day) = parse_weekday(word) {
+                    days.push(day);
+                } else if let Some(n) = parse_ordinal(word) {
+                    if ordinal.replace(n).is_some() {
+                        return Err("give a single day of the month".to_string());
+                    }
+                } else if let Ok(n) = word.parse::<u32>() {
+                    count = Some(n);
+                } else if let Some((found, used)) = parse_time(word, words.get(i).copied(), false) {
+                    set_time(found)?;
+                    i += used;
+                } else {
+                    return Err(format!(
+                        "did not understand '{}' in '{}'",
+                        word,
+                        text.trim()
+                    ));
+                }
+            }
+        }
+    }
+
+    let Some(time) = time else {
+        return Err(format!("'{}' needs a time, e.g. 'at 9:30'", text.trim()));
+    };
+    // "the 15 of every month"
+    if monthly && ordinal.is_none() {
+        ordinal = count.take().map(|n| n as i32);
+    }
+    if count.is_some() {
+        return Err("only weeks can be counted, e.g. 'every 2 weeks on Friday'".to_string());
+    }
+    days.sort_by_key(Weekday::num_days_from_monday);
+    days.dedup();
+    if every_day && !days.is_empty() {
+        return Err("say either 'every day' or which days".to_string());
+    }
+    if every_day && ordinal.is_none() {
+        days = WEEK.to_vec();
+    }
+    let at = time.format("%H:%M");
+
+    if monthly {
+        if weeks.is_some() {
+            return Err("say either weeks or months, not both".to_string());
+        }
+        let Some(ordinal) = ordinal else {
+            return Err(
+                "say which day of the month, e.g. 'first Monday of the month' or 'the 15th of every month'"
+                    .to_string(),
+            );
+        };
+        if start.is_some() {
+            return Err("a start date only applies to 'every N weeks'".to_string());
+        }
+        let nth = match ordinal {
+            -1 => "last".to_string(),
+            n => ORDINALS[n.clamp(1, 5) as usize - 1].to_string(),
+        };
+        let mut rule = Rule::new(Freq::Monthly);
+        let meaning = match days.as_slice() {
+            [] if (1..=31).contains(&ordinal) => {
+                let expression = format!(
+                    "0 {} {} {} * *",
+                    time.format("%-M"),
+                    time.format("%-H"),
+                    ordinal
+                );
+                return Ok(Phrase {
+                    schedule: cron(&expression)?,
+                    meaning: format!(
+                        "on the {} of every month at {}{}",
+                        numbered(ordinal as u32),
+                        at,
+                        if ordinal > 28 {
+                            " (months without one are skipped)"
+                        } else {
+                            ""
+                        }
+                    ),
+                });
+            }
+            [day] if (1..=5).contains(&ordinal) => {
+                let first = (ordinal - 1) * 7 + 1;
+                let expression = format!(
+                    "0 {} {} {}-{} * {}",
+                    time.format("%-M"),
+                    time.format("%-H"),
+                    first,
+                    (first + 6).min(31),
+                    day
+                );
+                return Ok(Phrase {
+                    schedule: cron(&expression)?,
+                    meaning: format!(
+                        "on the {} {} of every month at {}{}",
+                        nth,
+                        full_name(*day),
+                        at,
+                        if ordinal == 5 {
+                            " (months without one are skipped)"
+                        } else {
+                            ""
+                        }
+                    ),
+                });
+            }
+            [] if ordinal == -1 => {
+                rule.by_month_day = vec![-1];
+                format!("on the last day of every month at {}", at)
+            }
+            [day] if ordinal == -1 => {
+                rule.by_day = vec![ByDay {
+                    nth: Some(-1),
+                    weekday: *day,
+                }];
+                format!("on the last {} of every month at {}", full_name(*day), at)
+            }
+            weekdays if weekdays == &WEEK[..5] && (ordinal == -1 || (1..=5).contains(&ordinal)) => {
+                rule.by_day = weekdays
+                    .iter()
+                    .map(|day| ByDay {
+                        nth: None,
+                        weekday: *day,
+                    })
+                    .collect();
+                rule.by_set_pos = vec![ordinal];
+                format!(
+                    "on the {} business day (Monday to Friday) of every month at {}",
+                    nth, at
+                )
+            }
+            [] => return Err(format!("there is no day {} in a month", ordinal)),
+            [_] => return Err("use first to fifth or last for a weekday of the month".to_string()),
+            _ => return Err("give a single weekday for a day of the month".to_string()),
+        };
+        rule.by_hour = vec![time.hour()];
+        rule.by_minute = vec![time.minute()];
+        return Ok(Phrase {
+            schedule: Schedule::Rule(Box::new(rule)),
+            meaning,
+        });
+    }
+
+    if ordinal.is_some() {
+        return Err(
+            "a numbered day needs 'of the month', e.g. 'the 1st of every month'".to_string(),
+        );
+    }
+    if days.is_empty() {
+        return Err(
+            "say which days, e.g. 'every weekday' or 'every Monday and Thursday'".to_string(),
+        );
+    }
+    match weeks.unwrap_or(1) {
+        0 => Err("every 0 weeks never fires".to_string()),
+        weeks if weeks > MAX_INTERVAL => Err(format!(
+            "count at most {} weeks, e.g. 'every 2 weeks on Friday'",
+            MAX_INTERVAL
+        )),
+        1 => {
+            if start.is_some() {
+                return Err("a start date only applies to 'every N weeks'".to_string());
+            }
+            let day_of_week = if days.len() == 7 {
+                "*".to_string()
+            } else {
+                days.iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+            let expression = format!(
+                "0 {} {} * * {}",
+                time.format("%-M"),
+                time.format("%-H"),
+                day_of_week
+            );
+            Ok(Phrase {
+                schedule: cron(&expression)?,
+                meaning: format!("every {} at {}", describe_days(&days), at),
+            })
+        }
+        weeks => {
+            let counted = match start {
+                Some(start) => format!(", counting from the week of {}", start),
+                None => " - add 'starting <date>' to choose which weeks".to_string(),
+            };
+            Ok(Phrase {
+                meaning: format!(
+                    "every {} weeks on {} at {}{}",
+                    weeks,
+                    list(days.iter().map(|day| full_name(*day))),
+                    at,
+                    counted
+                ),
+                schedule: Schedule::Rule(Box::new(Rule {
+                    interval: weeks,
+                    by_day: days
+                        .into_iter()
+                        .map(|weekday| ByDay { nth: None, weekday })
+                        .collect(),
+                    by_hour: vec![time.hour()],
+                    by_minute: vec![time.minute()],
+                    start: start.map(|start| start.and_time(NaiveTime::MIN)),
+                    ..Rule::new(Freq::Weekly)
+                })),
+            })
+        }
+    }
+}
+
+fn cron(expression: &str) -> Result<Schedule, String> {
+    cron::Schedule::from_str(expression)
+        .map(|cron| Schedule::Cron(Box::new(cron)))
+        .map_err(|e| format!("could not build cron expression '{}': {}", expression, e))
+}
+
+/// A time such as `9`, `9am`, `9:30`, `9.30pm` or `16:00`, possibly followed
+/// by a separate `am`/`pm` in `next`. A bare hour only counts as a time when
+/// `bare_hour` is set (after "at"). Returns the time and whether `next` was
+/// used.
+fn parse_time(word: &str, next: Option<&str>, bare_hour: bool) -> Option<(NaiveTime, usize)> {
+    let (clock, suffix, used) = if let Some(clock) = word.strip_suffix("am") {
+        (clock, Some(false), 0)
+    } else if let Some(clock) = word.strip_suffix("pm") {
+        (clock, Some(true), 0)
+    } else {
+        match next {
+            Some("am") => (word, Some(false), 1),
+            Some("pm") => (word, Some(true), 1),
+            _ => (word, None, 0),
+        }
+    };
+    let (hour, minute) = match clock.split_once([':', '.']) {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute.parse().ok()?),
+        Some(_) => return None,
+        None if suffix.is_some() || bare_hour => (clock, 0),
+        None => return None,
+    };
+    let mut hour: u32 = hour.parse().ok()?;
+    match suffix {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(false) if hour == 12 => hour = 0,
+        Some(true) if hour != 12 => hour += 12,
+        _ => {}
+    }
+    NaiveTime::from_hms_opt(hour, minute, 0).map(|time| (time, used))
+}
+
+/// "monday", "mondays", "mon", "tues", …
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "tues" => Some(Weekday::Tue),
+        "weds" => Some(Weekday::Wed),
+        "thur" | "thurs" => Some(Weekday::Thu),
+        _ => Weekday::from_str(word)
+            .ok()
+            .or_else(|| Weekday::from_str(word.strip_suffix('s')?).ok()),
+    }
+}
+
+/// "first" to "fifth", "last" (-1), or "1st", "2nd", "15th", …
+fn parse_ordinal(word: &str) -> Option<i32> {
+    if word == "last" {
+        return Some(-1);
+    }
+    if let Some(index) = ORDINALS.iter().position(|ordinal| *ordinal == word) {
+        return Some(index as i32 + 1);
+    }
+    let digits = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| word.strip_suffix(suffix))?;
+    digits.parse().ok()
+}
+
+fn numbered(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+fn describe_days(days: &[Weekday]) -> String {
+    match days {
+        _ if days == WEEK => "day".to_string(),
+        _ if days == &WEEK[..5] => "weekday (Monday to Friday)".to_string(),
+        _ if days == &WEEK[5..] => "weekend day (Saturday and Sunday)".to_string(),
+        _ => list(days.iter().map(|day| full_name(*day))),
+    }
+}
+
+fn full_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// "a", "a and b", "a, b and c".
+fn list<'a>(items: impl Iterator<Item = &'a str>) -> String {
+    let items: Vec<&str> = items.collect();
+    match items.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cron_of(text: &str) -> String {
+        match parse(text).unwrap().schedule {
+            Schedule::Cron(cron) => cron.to_string(),
+            other => panic!("expected a cron schedule, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn weekdays_and_named_days() {
+        assert_eq!(
+            cron_of("every weekday at 9:30"),
+            "0 30 9 * * Mon,Tue,Wed,Thu,Fri"
+        );
+        assert_eq!(
+            cron_of("Mondays and Thursdays at 4 pm"),
+            "0 0 16 * * Mon,Thu"
+        );
+        assert_eq!(cron_of("every day at noon"), "0 0 12 * * *");
+        assert_eq!(cron_of("at 12am every weekend"), "0 0 0 * * Sat,Sun");
+        assert_eq!(
+            parse("every weekday at 9:30").unwrap().meaning,
+            "every weekday (Monday to Friday) at 09:30"
+        );
+    }
+
+    #[test]
+    fn days_of_the_month() {
+        assert_eq!(
+            cron_of("first Monday of the month at 10am"),
+            "0 0 10 1-7 * Mon"
+        );
+        assert_eq!(
+            cron_of("every month on the 2nd tuesday at 9"),
+            "0 0 9 8-14 * Tue"
+        );
+        assert_eq!(cron_of("the 15th of every month at 8:05"), "0 5 8 15 * *");
+        assert_eq!(
+            cron_of("fifth friday of the month at noon"),
+            "0 0 12 29-31 * Fri"
+        );
+        assert_eq!(
+            parse("first Monday of the month at 10am").unwrap().meaning,
+            "on the first Monday of every month at 10:00"
+        );
+    }
+
+    #[test]
+    fn every_few_weeks() {
+        let phrase = parse("every 2 weeks on Friday at 4pm starting 2026-10-16").unwrap();
+        let text = phrase.schedule.to_string();
+        assert_eq!(
+            text,
+            "DTSTART:20261016T000000 RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=FR;BYHOUR=16;BYMINUTE=0"
+        );
+        // The text form reads back as the same schedule
+        assert_eq!(Schedule::from_str(&text).unwrap(), phrase.schedule);
+        assert_eq!(cron_of("every week on wed at 9am"), "0 0 9 * * Wed");
+        assert!(parse("every 1000 weeks on friday at 9").is_ok());
+    }
+
+    #[test]
+    fn last_days_of_the_month() {
+        let rule_of = |text: &str| match parse(text).unwrap().schedule {
+            Schedule::Rule(rule) => rule.to_string(),
+            other => panic!("expected a recurrence rule, got {:?}", other),
+        };
+        assert_eq!(
+            rule_of("last friday of the month at 9"),
+            "RRULE:FREQ=MONTHLY;BYDAY=-1FR;BYHOUR=9;BYMINUTE=0"
+        );
+        assert_eq!(
+            rule_of("the last business day of every month at 5pm"),
+            "RRULE:FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYHOUR=17;BYMINUTE=0;BYSETPOS=-1"
+        );
+        assert_eq!(
+            rule_of("last day of the month at 8:30"),
+            "RRULE:FREQ=MONTHLY;BYMONTHDAY=-1;BYHOUR=8;BYMINUTE=30"
+        );
+        assert_eq!(
+            parse("first working day of the month at 9am")
+                .unwrap()
+                .meaning,
+            "on the first business day (Monday to Friday) of every month at 09:00"
+        );
+    }
+
+    #[test]
+    fn rejects_unclear_phrases() {
+        for text in [
+            "every weekday",
+            "every 3 days at 9",
+            "every fortnight at 9",
+            "the 32nd of every month at 9",
+            "last monday and friday of the month at 9",
+            "every blue moon at 9",
+            "every monday at 9 and 10",
+            "every monday at 25:00",
+            "every 2 weeks at 9",
+            "every 1001 weeks on friday at 9",
+            "every 99999999 weeks on friday at 9",
+        ] {
+            assert!(parse(text).is_err(), "{} should not parse", text);
+        }
+    }
+}
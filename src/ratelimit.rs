@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{sleep_until, Instant};
+
+/// `chat.postMessage` is limited to about one post per second per channel
+/// rather than by tier.
+const POSTS_PER_CHANNEL_PER_MINUTE: u32 = 60;
+
+/// Spaces requests evenly so one workspace stays under its configured rate.
+/// Callers queue on the mutex, so slots are handed out in arrival order.
+pub struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(requests: u32) -> Self {
+        RateLimiter {
+            interval: Duration::from_secs(60) / requests.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for the next free slot.
+    pub async fn acquire(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            sleep_until(*next).await;
+        }
+        *next = (*next).max(now) + self.interval;
+    }
+}
+
+/// Requests per minute Slack allows a workspace for each method the bot
+/// calls, per the method's rate limit tier.
+fn tier_limit(method: &str) -> Option<u32> {
+    match method {
+        // Tier 1
+        "apps.connections.open" => Some(1),
+        // Tier 2
+        "conversations.list" | "usergroups.users.list" => Some(20),
+        // Tier 3
+        "chat.update" | "conversations.open" | "users.lookupByEmail" => Some(50),
+        _ => None,
+    }
+}
+
+/// A [`RateLimiter`] per method, and per channel for `chat.postMessage`,
+/// created on first use, so bursts of reminders queue up instead of
+/// tripping Slack's limits.
+#[derive(Default)]
+pub struct MethodLimits {
+    limiters: std::sync::Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+impl MethodLimits {
+    /// Wait for the next free slot for `method`; `channel` is the channel
+    /// posted to.
+    pub async fn acquire(&self, method: &str, channel: Option<&str>) {
+        let (key, per_minute) = match (method, channel) {
+            ("chat.postMessage", Some(channel)) => (
+                format!("{} {}", method, channel),
+                POSTS_PER_CHANNEL_PER_MINUTE,
+            ),
+            _ => match tier_limit(method) {
+                Some(per_minute) => (method.to_string(), per_minute),
+                None => return,
+            },
+        };
+        let limiter = Arc::clone(
+            self.limiters
+                .lock()
+                .unwrap()
+                .entry(key)
+                .or_insert_with(|| Arc::new(RateLimiter::per_minute(per_minute))),
+        );
+        limiter.acquire().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_methods_by_tier() {
+        assert_eq!(tier_limit("apps.connections.open"), Some(1));
+        assert_eq!(tier_limit("conversations.list"), Some(20));
+        assert_eq!(tier_limit("users.lookupByEmail"), Some(50));
+        assert_eq!(tier_limit("chat.postMessage"), None);
+        assert_eq!(tier_limit("auth.test"), None);
+    }
+
+    #[tokio::test]
+    async fn hands_out_evenly_spaced_slots_in_arrival_order() {
+        let limiter = Arc::new(RateLimiter::per_minute(600));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..4)
+            .map(|index| {
+                let limiter = Arc::clone(&limiter);
+                let order = Arc::clone(&order);
+                tokio::spawn(async move {
+                    limiter.acquire().await;
+                    order.lock().await.push(index);
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        // The first slot is free, the other three are 100ms apart
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert_eq!(*order.lock().await, [0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn spaces_posts_per_channel_only() {
+        let limits = MethodLimits::default();
+        let start = Instant::now();
+        limits.acquire("chat.postMessage", Some("C1")).await;
+        limits.acquire("chat.postMessage", Some("C2")).await;
+        for _ in 0..10 {
+            limits.acquire("auth.test", None).await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(500));
+
+        // The second post to C1 waits for the next once-a-second slot
+        limits.acquire("chat.postMessage", Some("C1")).await;
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+}
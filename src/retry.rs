@@ -0,0 +1,184 @@
+use crate::slack::SlackError;
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+/// How a failed Slack call is retried.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the second attempt; doubled for every further attempt.
+    pub base_delay: Duration,
+    /// Upper bound for a single backoff delay.
+    pub max_delay: Duration,
+    /// Fraction (0.0..=1.0) of each delay that is randomized.
+    pub jitter: f64,
+    /// Overall time budget, measured from the first attempt.
+    pub deadline: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            jitter: 0.2,
+            deadline: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff before attempt `attempt + 1`, with jitter applied.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        if self.jitter <= 0.0 {
+            return delay;
+        }
+        let spread = rand::thread_rng().gen_range(-self.jitter..=self.jitter);
+        delay.mul_f64((1.0 + spread).max(0.0))
+    }
+
+    /// Run `op` until it succeeds, fails with a non-retryable error, or the
+    /// attempt count or deadline is exhausted. `label` identifies the call in
+    /// the logs.
+    pub async fn run<T, F, Fut>(&self, label: &str, mut op: F) -> Result<T, SlackError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, SlackError>>,
+    {
+        let started = Instant::now();
+        let mut attempt = 1;
+        loop {
+            println!(
+                "Sending '{}' (attempt {}/{})",
+                label, attempt, self.max_attempts
+            );
+            let error = match op().await {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+
+            if !error.is_retryable() {
+                eprintln!(
+                    "Attempt {} for '{}' failed with a non-retryable error: {}",
+                    attempt, label, error
+                );
+                return Err(error);
+            }
+            if attempt >= self.max_attempts {
+                eprintln!(
+                    "Attempt {} for '{}' failed, giving up: {}",
+                    attempt, label, error
+                );
+                return Err(error);
+            }
+
+            // Slack's Retry-After wins over our own backoff on 429s.
+            let delay = match &error {
+                SlackError::RateLimited {
+                    retry_after: Some(after),
+                } => *after,
+                _ => self.backoff(attempt),
+            };
+            if started.elapsed() + delay > self.deadline {
+                eprintln!(
+                    "Attempt {} for '{}' failed, retry deadline of {:?} reached: {}",
+                    attempt, label, self.deadline, error
+                );
+                return Err(error);
+            }
+
+            eprintln!(
+                "Attempt {} for '{}' failed: {}. Retrying in {:.1}s",
+                attempt,
+                label,
+                error,
+                delay.as_secs_f64()
+            );
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+            jitter: 0.0,
+            deadline: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_max_delay() {
+        let policy = policy();
+        let delays: Vec<u64> = (1..=5)
+            .map(|attempt| policy.backoff(attempt).as_secs())
+            .collect();
+        assert_eq!(delays, [1, 2, 4, 5, 5]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(5));
+
+        let jittered = RetryPolicy {
+            jitter: 0.5,
+            ..policy
+        };
+        for attempt in 1..=5 {
+            let delay = jittered.backoff(attempt);
+            let expected = policy.backoff(attempt);
+            assert!(delay >= expected.mul_f64(0.5) && delay <= expected.mul_f64(1.5));
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_after_replaces_the_backoff() {
+        // The backoff alone would blow the deadline; Slack's short
+        // Retry-After does not
+        let policy = RetryPolicy {
+            base_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(10),
+            ..policy()
+        };
+        let started = Instant::now();
+        let mut calls = 0;
+        let result = policy
+            .run("test", || {
+                calls += 1;
+                let first = calls == 1;
+                async move {
+                    if first {
+                        Err(SlackError::RateLimited {
+                            retry_after: Some(Duration::from_millis(20)),
+                        })
+                    } else {
+                        Ok(())
+                    }
+                }
+            })
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(calls, 2);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // Without Retry-After the deadline check gives up straight away
+        let result = policy
+            .run("test", || async {
+                Err::<(), _>(SlackError::RateLimited { retry_after: None })
+            })
+            .await;
+        assert!(matches!(result, Err(SlackError::RateLimited { .. })));
+    }
+}
@@ -0,0 +1,834 @@
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+/// Periods (days, weeks, months or years) searched for the next occurrence
+/// before giving up.
+const MAX_PERIODS: usize = 10_000;
+
+/// Largest `INTERVAL`, also the most weeks a phrase may count.
+pub const MAX_INTERVAL: u32 = 1000;
+
+/// Longest `BYSETPOS` offset, from either end of a period.
+const MAX_SET_POS: i32 = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Freq {
+    fn unit(self) -> &'static str {
+        match self {
+            Freq::Daily => "day",
+            Freq::Weekly => "week",
+            Freq::Monthly => "month",
+            Freq::Yearly => "year",
+        }
+    }
+}
+
+/// A `BYDAY` entry: a weekday, or with `nth` the nth one (negative: counted
+/// from the end) of the month, or of the year for yearly rules without
+/// `BYMONTH`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub nth: Option<i32>,
+    pub weekday: Weekday,
+}
+
+/// An RFC 5545 recurrence rule (`RRULE`) with its `DTSTART`, evaluated on
+/// wall-clock time. Written as `[DTSTART:20260104T140000] RRULE:FREQ=…`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub freq: Freq,
+    pub interval: u32,
+    pub by_month: Vec<u32>,
+    pub by_month_day: Vec<i32>,
+    pub by_day: Vec<ByDay>,
+    pub by_hour: Vec<u32>,
+    pub by_minute: Vec<u32>,
+    pub by_set_pos: Vec<i32>,
+    pub week_start: Weekday,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDateTime>,
+    /// `DTSTART`: intervals and `COUNT` are counted from it, the time of
+    /// day defaults to it, and nothing fires before it. Without it
+    /// intervals count from Monday 1970-01-05 at midnight.
+    pub start: Option<NaiveDateTime>,
+}
+
+impl Rule {
+    /// A rule at `freq` with no `BY…` parts, firing at the anchor's time.
+    pub fn new(freq: Freq) -> Self {
+        Rule {
+            freq,
+            interval: 1,
+            by_month: Vec::new(),
+            by_month_day: Vec::new(),
+            by_day: Vec::new(),
+            by_hour: Vec::new(),
+            by_minute: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+            count: None,
+            until: None,
+            start: None,
+        }
+    }
+
+    fn anchor(&self) -> NaiveDateTime {
+        self.start.unwrap_or_else(|| {
+            NaiveDate::from_ymd_opt(1970, 1, 5)
+                .expect("valid date")
+                .and_time(NaiveTime::MIN)
+        })
+    }
+
+    /// Occurrences at or after `from`, in order.
+    pub fn occurrences(&self, from: NaiveDateTime) -> impl Iterator<Item = NaiveDateTime> + '_ {
+        let interval = i64::from(self.interval);
+        // COUNT has to be counted from the first occurrence on
+        let mut period = match self.count {
+            Some(_) => 0,
+            None => self.period_of(from.date()).max(0),
+        };
+        period -= period % interval;
+        let mut buffer = VecDeque::new();
+        let mut fired = 0;
+        let mut searched = 0;
+        std::iter::from_fn(move || loop {
+            if let Some(next) = buffer.pop_front() {
+                if self.count.is_some_and(|count| fired >= count)
+                    || self.until.is_some_and(|until| next > until)
+                {
+                    return None;
+                }
+                fired += 1;
+                if next >= from {
+                    return Some(next);
+                }
+                continue;
+            }
+            if searched >= MAX_PERIODS {
+                return None;
+            }
+            searched += 1;
+            // Past the last representable date
+            buffer.extend(self.expand(period)?);
+            period += interval;
+        })
+    }
+
+    /// Index of the period containing `date`, counted from the anchor's.
+    fn period_of(&self, date: NaiveDate) -> i64 {
+        let anchor = self.anchor().date();
+        match self.freq {
+            Freq::Daily => (date - anchor).num_days(),
+            Freq::Weekly => (self.week_of(date) - self.week_of(anchor)).num_days() / 7,
+            Freq::Monthly => {
+                let months =
+                    |date: NaiveDate| i64::from(date.year()) * 12 + i64::from(date.month0());
+                months(date) - months(anchor)
+            }
+            Freq::Yearly => i64::from(date.year() - anchor.year()),
+        }
+    }
+
+    /// First day of the week containing `date`.
+    fn week_of(&self, date: NaiveDate) -> NaiveDate {
+        let offset = date.weekday().days_since(self.week_start);
+        date - Days::new(u64::from(offset))
+    }
+
+    /// The occurrences in period `period`, sorted, or `None` once the
+    /// period lies past the last date chrono can represent.
+    fn expand(&self, period: i64) -> Option<Vec<NaiveDateTime>> {
+        let anchor = self.anchor();
+        let period = u64::try_from(period).ok()?;
+        let dates = match self.freq {
+            Freq::Daily => {
+                let date = anchor.date().checked_add_days(Days::new(period))?;
+                match self.in_month(date) && self.on_month_day(date) && self.on_day(date) {
+                    true => vec![date],
+                    false => Vec::new(),
+                }
+            }
+            Freq::Weekly => {
+                let first = self
+                    .week_of(anchor.date())
+                    .checked_add_days(Days::new(period.checked_mul(7)?))?;
+                first
+                    .iter_days()
+                    .take(7)
+                    .filter(|date| self.in_month(*date))
+                    .filter(|date| match self.by_day.is_empty() {
+                        true => date.weekday() == anchor.weekday(),
+                        false => self.on_day(*date),
+                    })
+                    .collect()
+            }
+            Freq::Monthly => {
+                let first = anchor.date().with_day(1).expect("valid date");
+                let month = first.checked_add_months(Months::new(u32::try_from(period).ok()?))?;
+                match self.in_month(month) {
+                    true => self.month_dates(month),
+                    false => Vec::new(),
+                }
+            }
+            Freq::Yearly => {
+                let year = anchor.year().checked_add(i32::try_from(period).ok()?)?;
+                NaiveDate::from_ymd_opt(year, 1, 1)?;
+                self.year_dates(year)
+            }
+        };
+
+        let clock = self.times_of_day();
+        let mut times: Vec<NaiveDateTime> = dates
+            .iter()
+            .flat_map(|date| {
+                clock
+                    .iter()
+                    .filter_map(|(hour, minute)| date.and_hms_opt(*hour, *minute, anchor.second()))
+            })
+            .collect();
+        times.sort();
+        times.dedup();
+
+        if !self.by_set_pos.is_empty() {
+            let len = times.len() as i32;
+            let mut picked: Vec<NaiveDateTime> = self
+                .by_set_pos
+                .iter()
+                .filter_map(|pos| {
+                    let index = if *pos > 0 { pos - 1 } else { len + pos };
+                    (0..len).contains(&index).then(|| times[index as usize])
+                })
+                .collect();
+            picked.sort();
+            picked.dedup();
+            times = picked;
+        }
+        times.retain(|time| *time >= anchor);
+        Some(times)
+    }
+
+    /// (hour, minute) pairs from `BYHOUR` and `BYMINUTE`, each defaulting
+    /// to the anchor's.
+    fn times_of_day(&self) -> Vec<(u32, u32)> {
+        let anchor = self.anchor();
+        let hours = match self.by_hour.is_empty() {
+            true => vec![anchor.hour()],
+            false => self.by_hour.clone(),
+        };
+        let minutes = match self.by_minute.is_empty() {
+            true => vec![anchor.minute()],
+            false => self.by_minute.clone(),
+        };
+        hours
+            .iter()
+            .flat_map(|hour| minutes.iter().map(move |minute| (*hour, *minute)))
+            .collect()
+    }
+
+    /// Days of the month starting at `first` matching `BYMONTHDAY` and
+    /// `BYDAY`, or the anchor's day of the month without either.
+    fn month_dates(&self, first: NaiveDate) -> Vec<NaiveDate> {
+        let days: Vec<NaiveDate> = first
+            .iter_days()
+            .take_while(|date| date.month() == first.month())
+            .collect();
+        if self.by_month_day.is_empty() && self.by_day.is_empty() {
+            return days
+                .into_iter()
+                .filter(|date| date.day() == self.anchor().day())
+                .collect();
+        }
+        days.into_iter()
+            .filter(|date| self.on_month_day(*date) && self.on_day(*date))
+            .collect()
+    }
+
+    fn year_dates(&self, year: i32) -> Vec<NaiveDate> {
+        let Some(first) = NaiveDate::from_ymd_opt(year, 1, 1) else {
+            return Vec::new();
+        };
+        let anchor = self.anchor();
+        if !self.by_month.is_empty() || !self.by_month_day.is_empty() {
+            // Month by month, with BYDAY ordinals counted within the month
+            return (0..12)
+                .filter_map(|month| first.checked_add_months(Months::new(month)))
+                .filter(|month| self.in_month(*month))
+                .flat_map(
+                    |month| match self.by_month_day.is_empty() && self.by_day.is_empty() {
+                        true => month.with_day(anchor.day()).into_iter().collect(),
+                        false => self.month_dates(month),
+                    },
+                )
+                .collect();
+        }
+        if self.by_day.is_empty() {
+            return first
+                .with_month(anchor.month())
+                .and_then(|date| date.with_day(anchor.day()))
+                .into_iter()
+                .collect();
+        }
+        first
+            .iter_days()
+            .take_while(|date| date.year() == year)
+            .filter(|date| {
+                self.by_day.iter().any(|by_day| {
+                    let (position, from_end) =
+                        (date.ordinal0(), days_in_year(year) - date.ordinal());
+                    matches_day(by_day, *date, position, from_end)
+                })
+            })
+            .collect()
+    }
+
+    fn in_month(&self, date: NaiveDate) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&date.month())
+    }
+
+    fn on_month_day(&self, date: NaiveDate) -> bool {
+        if self.by_month_day.is_empty() {
+            return true;
+        }
+        let last = days_in_month(date) as i32;
+        self.by_month_day.iter().any(|day| {
+            let day = if *day > 0 { *day } else { last + 1 + day };
+            day == date.day() as i32
+        })
+    }
+
+    /// Whether `date` matches `BYDAY`, with ordinals counted in its month.
+    fn on_day(&self, date: NaiveDate) -> bool {
+        if self.by_day.is_empty() {
+            return true;
+        }
+        let position = date.day0();
+        let from_end = days_in_month(date) - date.day();
+        self.by_day
+            .iter()
+            .any(|by_day| matches_day(by_day, date, position, from_end))
+    }
+
+    /// The rule in plain words, e.g. "every 2 weeks on Sunday at 14:00".
+    pub fn describe(&self) -> String {
+        let unit = self.freq.unit();
+        let mut text = match self.interval {
+            1 => format!("every {}", unit),
+            n => format!("every {} {}s", n, unit),
+        };
+        if !self.by_month.is_empty() {
+            let months: Vec<String> = self
+                .by_month
+                .iter()
+                .filter_map(|month| NaiveDate::from_ymd_opt(2000, *month, 1))
+                .map(|date| date.format("%B").to_string())
+                .collect();
+            text.push_str(&format!(" in {}", list(&months)));
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self
+                .by_month_day
+                .iter()
+                .map(|day| month_day(*day))
+                .collect();
+            text.push_str(&format!(" on {}", list(&days)));
+        }
+        if !self.by_day.is_empty() {
+            let of = match (self.freq, self.by_month.is_empty()) {
+                (Freq::Yearly, true) => "year",
+                _ => "month",
+            };
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|by_day| match by_day.nth {
+                    None => weekday_name(by_day.weekday).to_string(),
+                    Some(nth) => format!(
+                        "the {} {} of the {}",
+                        position(nth),
+                        weekday_name(by_day.weekday),
+                        of
+                    ),
+                })
+                .collect();
+            let on = if self.by_month_day.is_empty() {
+                " on"
+            } else {
+                ", if a"
+            };
+            text.push_str(&format!("{} {}", on, list(&days)));
+        }
+        if !self.by_set_pos.is_empty() {
+            let positions: Vec<String> = self.by_set_pos.iter().map(|pos| position(*pos)).collect();
+            text.push_str(&format!(
+                ", only the {} of those each {}",
+                list(&positions),
+                unit
+            ));
+        }
+        let times: Vec<String> = self
+            .times_of_day()
+            .iter()
+            .map(|(hour, minute)| format!("{:02}:{:02}", hour, minute))
+            .collect();
+        text.push_str(&format!(" at {}", list(&times)));
+        if let Some(start) = self.start {
+            text.push_str(&format!(", starting {}", start.format("%Y-%m-%d %H:%M")));
+        }
+        if let Some(count) = self.count {
+            text.push_str(&format!(", {} times", count));
+        }
+        if let Some(until) = self.until {
+            text.push_str(&format!(", until {}", until.format("%Y-%m-%d %H:%M")));
+        }
+        text
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// `RRULE:FREQ=…` or just `FREQ=…`, optionally after a `DTSTART:…`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut start = None;
+        let mut rule = None;
+        for part in text.split_whitespace() {
+            let part = part.to_ascii_uppercase();
+            if let Some(value) = part.strip_prefix("DTSTART:") {
+                start = Some(parse_datetime(value)?);
+            } else if let Some(value) = part.strip_prefix("RRULE:") {
+                rule = Some(value.to_string());
+            } else if part.starts_with("FREQ=") {
+                rule = Some(part);
+            } else {
+                return Err(format!("'{}' is not part of a recurrence rule", part));
+            }
+        }
+        let rule = rule.ok_or("missing RRULE:FREQ=…")?;
+
+        let mut freq = None;
+        let mut parsed = Rule {
+            start,
+            ..Rule::new(Freq::Daily)
+        };
+        for pair in rule.split(';').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("'{}' is not a NAME=VALUE part", pair))?;
+            match key {
+                "FREQ" => {
+                    freq = Some(match value {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        "MONTHLY" => Freq::Monthly,
+                        "YEARLY" => Freq::Yearly,
+                        other => {
+                            return Err(format!(
+                                "FREQ={} is not supported; use DAILY, WEEKLY, MONTHLY or YEARLY (or `every` for shorter intervals)",
+                                other
+                            ))
+                        }
+                    })
+                }
+                "INTERVAL" => parsed.interval = number(key, value, 1..=MAX_INTERVAL)?,
+                "COUNT" => parsed.count = Some(number(key, value, 1..=100_000)?),
+                "UNTIL" => parsed.until = Some(parse_datetime(value)?),
+                "BYMONTH" => parsed.by_month = list_of(key, value, |v| number(key, v, 1..=12))?,
+                "BYMONTHDAY" => {
+                    parsed.by_month_day = list_of(key, value, |v| signed(key, v, 31))?
+                }
+                "BYDAY" => parsed.by_day = list_of(key, value, parse_by_day)?,
+                "BYHOUR" => parsed.by_hour = list_of(key, value, |v| number(key, v, 0..=23))?,
+                "BYMINUTE" => parsed.by_minute = list_of(key, value, |v| number(key, v, 0..=59))?,
+                "BYSETPOS" => {
+                    parsed.by_set_pos = list_of(key, value, |v| signed(key, v, MAX_SET_POS))?
+                }
+                "WKST" => parsed.week_start = parse_weekday(value)?,
+                other => return Err(format!("{} is not supported in a recurrence rule", other)),
+            }
+        }
+        parsed.freq = freq.ok_or("a recurrence rule needs FREQ")?;
+
+        if parsed.count.is_some() && parsed.until.is_some() {
+            return Err("set COUNT or UNTIL, not both".to_string());
+        }
+        if parsed.count.is_some() && parsed.start.is_none() {
+            return Err("COUNT needs a DTSTART to count from".to_string());
+        }
+        let ordinals = parsed.by_day.iter().any(|by_day| by_day.nth.is_some());
+        if ordinals && !matches!(parsed.freq, Freq::Monthly | Freq::Yearly) {
+            return Err("numbered BYDAY entries need FREQ=MONTHLY or YEARLY".to_string());
+        }
+        if parsed.freq == Freq::Weekly && !parsed.by_month_day.is_empty() {
+            return Err("BYMONTHDAY does not apply to FREQ=WEEKLY".to_string());
+        }
+        let other_by = !(parsed.by_month.is_empty()
+            && parsed.by_month_day.is_empty()
+            && parsed.by_day.is_empty()
+            && parsed.by_hour.is_empty()
+            && parsed.by_minute.is_empty());
+        if !parsed.by_set_pos.is_empty() && !other_by {
+            return Err("BYSETPOS needs another BY… part to pick from".to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(start) = self.start {
+            write!(f, "DTSTART:{} ", start.format("%Y%m%dT%H%M%S"))?;
+        }
+        write!(
+            f,
+            "RRULE:FREQ={}",
+            format!("{:?}", self.freq).to_uppercase()
+        )?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        let join = |values: Vec<String>| values.join(",");
+        let numbers = |values: &[u32]| join(values.iter().map(ToString::to_string).collect());
+        if !self.by_month.is_empty() {
+            write!(f, ";BYMONTH={}", numbers(&self.by_month))?;
+        }
+        if !self.by_month_day.is_empty() {
+            write!(
+                f,
+                ";BYMONTHDAY={}",
+                join(self.by_month_day.iter().map(ToString::to_string).collect())
+            )?;
+        }
+        if !self.by_day.is_empty() {
+            let days = self
+                .by_day
+                .iter()
+                .map(|by_day| {
+                    let code = &weekday_name(by_day.weekday)[..2].to_uppercase();
+                    match by_day.nth {
+                        Some(nth) => format!("{}{}", nth, code),
+                        None => code.to_string(),
+                    }
+                })
+                .collect();
+            write!(f, ";BYDAY={}", join(days))?;
+        }
+        if !self.by_hour.is_empty() {
+            write!(f, ";BYHOUR={}", numbers(&self.by_hour))?;
+        }
+        if !self.by_minute.is_empty() {
+            write!(f, ";BYMINUTE={}", numbers(&self.by_minute))?;
+        }
+        if !self.by_set_pos.is_empty() {
+            write!(
+                f,
+                ";BYSETPOS={}",
+                join(self.by_set_pos.iter().map(ToString::to_string).collect())
+            )?;
+        }
+        if self.week_start != Weekday::Mon {
+            write!(
+                f,
+                ";WKST={}",
+                &weekday_name(self.week_start)[..2].to_uppercase()
+            )?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%S"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `date` is `by_day`'s weekday and, for a numbered entry, the nth
+/// one given how many days of the month or year lie before (`position`) and
+/// after (`from_end`) it.
+fn matches_day(by_day: &ByDay, date: NaiveDate, position: u32, from_end: u32) -> bool {
+    date.weekday() == by_day.weekday
+        && match by_day.nth {
+            None => true,
+            Some(nth) if nth > 0 => (position / 7 + 1) as i32 == nth,
+            Some(nth) => (from_end / 7 + 1) as i32 == -nth,
+        }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    date.with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(1)))
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+fn days_in_year(year: i32) -> u32 {
+    NaiveDate::from_ymd_opt(year, 12, 31).map_or(365, |last| last.ordinal())
+}
+
+/// `20260104T140000` or `20260104`, as a wall-clock time (a trailing `Z` is
+/// ignored; times are read in the reminder's timezone).
+fn parse_datetime(value: &str) -> Result<NaiveDateTime, String> {
+    let trimmed = value.trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(trimmed, "%Y%m%dT%H%M%S")
+        .or_else(|_| {
+            NaiveDate::parse_from_str(trimmed, "%Y%m%d").map(|date| date.and_time(NaiveTime::MIN))
+        })
+        .map_err(|_| format!("'{}' is not a date like 20260104 or 20260104T140000", value))
+}
+
+fn number(key: &str, value: &str, range: std::ops::RangeInclusive<u32>) -> Result<u32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|number| range.contains(number))
+        .ok_or_else(|| {
+            format!(
+                "{}={} must be between {} and {}",
+                key,
+                value,
+                range.start(),
+                range.end()
+            )
+        })
+}
+
+/// A non-zero number within `-max..=max`.
+fn signed(key: &str, value: &str, max: i32) -> Result<i32, String> {
+    value
+        .parse::<i32>()
+        .ok()
+        .filter(|number| *number != 0 && number.abs() <= max)
+        .ok_or_else(|| format!("{}={} must be 1 to {} or -1 to -{}", key, value, max, max))
+}
+
+fn list_of<T>(
+    key: &str,
+    value: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Vec<T>, String> {
+    if value.is_empty() {
+        return Err(format!("{} is empty", key));
+    }
+    value.split(',').map(parse).collect()
+}
+
+/// `MO`, `-1FR`, `2TU`, …
+fn parse_by_day(value: &str) -> Result<ByDay, String> {
+    if !value.is_ascii() {
+        return Err(format!("'{}' is not a BYDAY entry like MO or -1FR", value));
+    }
+    let split = value.len().saturating_sub(2);
+    let (nth, code) = value.split_at(split);
+    let weekday = parse_weekday(code)?;
+    let nth = match nth {
+        "" => None,
+        nth => Some(
+            nth.trim_start_matches('+')
+                .parse::<i32>()
+                .ok()
+                .filter(|nth| *nth != 0 && nth.abs() <= 53)
+                .ok_or_else(|| format!("'{}' is not a BYDAY entry like MO or -1FR", value))?,
+        ),
+    };
+    Ok(ByDay { nth, weekday })
+}
+
+fn parse_weekday(code: &str) -> Result<Weekday, String> {
+    match code {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        other => Err(format!("'{}' is not a weekday like MO or FR", other)),
+    }
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// "first", "2nd", "last", "2nd to last", …
+fn position(nth: i32) -> String {
+    match nth {
+        1 => "first".to_string(),
+        -1 => "last".to_string(),
+        n if n > 0 => ordinal(n),
+        n => format!("{} to last", ordinal(-n)),
+    }
+}
+
+/// `BYMONTHDAY` in words: "the 15th", "the last day".
+fn month_day(day: i32) -> String {
+    match day {
+        -1 => "the last day".to_string(),
+        d if d < 0 => format!("the {} to last day", ordinal(-d)),
+        d => format!("the {}", ordinal(d)),
+    }
+}
+
+fn ordinal(n: i32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+fn list(items: &[String]) -> String {
+    match items.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next(rule: &str, from: &str, count: usize) -> Vec<String> {
+        let rule = Rule::from_str(rule).unwrap();
+        let from = NaiveDateTime::parse_from_str(from, "%Y-%m-%d %H:%M").unwrap();
+        rule.occurrences(from)
+            .take(count)
+            .map(|time| time.format("%a %Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn every_other_sunday_from_the_anchor() {
+        assert_eq!(
+            next(
+                "DTSTART:20260104T140000 RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=SU",
+                "2026-01-05 00:00",
+                3
+            ),
+            [
+                "Sun 2026-01-18 14:00",
+                "Sun 2026-02-01 14:00",
+                "Sun 2026-02-15 14:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn last_business_day_of_the_month() {
+        assert_eq!(
+            next(
+                "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;BYHOUR=17",
+                "2026-01-01 00:00",
+                3
+            ),
+            [
+                "Fri 2026-01-30 17:00",
+                "Fri 2026-02-27 17:00",
+                "Tue 2026-03-31 17:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn numbered_weekdays_and_month_days() {
+        assert_eq!(
+            next(
+                "RRULE:FREQ=MONTHLY;BYDAY=-1FR,2MO;BYHOUR=9",
+                "2026-03-01 00:00",
+                3
+            ),
+            [
+                "Mon 2026-03-09 09:00",
+                "Fri 2026-03-27 09:00",
+                "Mon 2026-04-13 09:00"
+            ]
+        );
+        assert_eq!(
+            next(
+                "FREQ=MONTHLY;BYMONTHDAY=-1;BYHOUR=8;BYMINUTE=30",
+                "2026-02-01 00:00",
+                2
+            ),
+            ["Sat 2026-02-28 08:30", "Tue 2026-03-31 08:30"]
+        );
+        assert_eq!(
+            next(
+                "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH;BYHOUR=12",
+                "2026-01-01 00:00",
+                2
+            ),
+            ["Thu 2026-11-26 12:00", "Thu 2027-11-25 12:00"]
+        );
+    }
+
+    #[test]
+    fn count_and_until_end_the_rule() {
+        let rule = "DTSTART:20260101T090000 RRULE:FREQ=DAILY;COUNT=3";
+        assert_eq!(next(rule, "2026-01-02 12:00", 5), ["Sat 2026-01-03 09:00"]);
+        assert_eq!(
+            next(
+                "FREQ=DAILY;BYHOUR=9;UNTIL=20260103T235959",
+                "2026-01-01 00:00",
+                5
+            ),
+            [
+                "Thu 2026-01-01 09:00",
+                "Fri 2026-01-02 09:00",
+                "Sat 2026-01-03 09:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn huge_intervals_end_instead_of_overflowing() {
+        let from = NaiveDateTime::parse_from_str("2026-01-01 00:00", "%Y-%m-%d %H:%M").unwrap();
+        for freq in [Freq::Daily, Freq::Weekly, Freq::Monthly, Freq::Yearly] {
+            let rule = Rule {
+                interval: u32::MAX,
+                ..Rule::new(freq)
+            };
+            assert_eq!(rule.occurrences(from).count(), 0, "{:?}", freq);
+        }
+    }
+
+    #[test]
+    fn round_trips_and_rejects() {
+        let text = "DTSTART:20260104T140000 RRULE:FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR;COUNT=4";
+        assert_eq!(Rule::from_str(text).unwrap().to_string(), text);
+        for bad in [
+            "FREQ=HOURLY",
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=DAILY;COUNT=3",
+            "FREQ=DAILY;BYSETPOS=1",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=MONTHLY;BYDAY=XX",
+            "FREQ=WEEKLY;BYDAY=€",
+            "FREQ=WEEKLY;BYDAY=1€",
+            "INTERVAL=2",
+        ] {
+            assert!(Rule::from_str(bad).is_err(), "{} should not parse", bad);
+        }
+    }
+}
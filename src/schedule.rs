@@ -0,0 +1,381 @@
+use crate::phrase;
+use crate::rrule::Rule;
+use chrono::offset::LocalResult;
+use chrono::{DateTime, Duration, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+use std::str::FromStr;
+
+/// Wall-clock formats accepted for one-off times and interval starts.
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+];
+
+/// Shortest interval an `every` schedule may use.
+const MIN_INTERVAL: Duration = Duration::minutes(1);
+/// Longest interval an `every` schedule may use, about 19 years.
+const MAX_INTERVAL: Duration = Duration::weeks(1000);
+
+/// When a reminder fires. Times are wall-clock times in the reminder's zone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Cron(Box<cron::Schedule>),
+    /// Fires once.
+    Once(NaiveDateTime),
+    /// Fires every `every` of elapsed time from `start`, or from midnight
+    /// on 1970-01-01 so that e.g. every 6h lands on 00:00, 06:00, …
+    Every {
+        every: Duration,
+        start: Option<NaiveDateTime>,
+    },
+    /// An RFC 5545 recurrence rule, for cadences cron cannot express such
+    /// as every other Sunday or the last business day of the month.
+    Rule(Box<Rule>),
+}
+
+impl Schedule {
+    /// Whether the reminder is done after its first fire.
+    pub fn is_one_off(&self) -> bool {
+        matches!(self, Schedule::Once(_))
+    }
+}
+
+/// The text form accepted by `FromStr`: a cron expression,
+/// `at 2026-11-03 15:00`, `every 90m [from 2026-11-03 09:00]`,
+/// `[DTSTART:…] RRULE:FREQ=…` or a phrase.
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Cron(cron) => write!(f, "{}", cron),
+            Schedule::Once(at) => write!(f, "at {}", at.format("%Y-%m-%d %H:%M")),
+            Schedule::Every { every, start } => {
+                write!(f, "every {}", format_interval(*every))?;
+                if let Some(start) = start {
+                    write!(f, " from {}", start.format("%Y-%m-%d %H:%M"))?;
+                }
+                Ok(())
+            }
+            Schedule::Rule(rule) => write!(f, "{}", rule),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        interpret(text).map(|(schedule, _)| schedule)
+    }
+}
+
+/// Parse `text` as `FromStr` does, also returning what it was understood to
+/// mean, for echoing back to whoever wrote it.
+pub fn interpret(text: &str) -> Result<(Schedule, String), String> {
+    let text = text.trim();
+    let starts_with_digit = |text: &str| text.starts_with(|c: char| c.is_ascii_digit());
+    if let Some(at) = text.strip_prefix("at ").filter(|at| at.contains('-')) {
+        let at = parse_datetime(at)?;
+        let meaning = format!("once, on {}", at.format("%a %Y-%m-%d at %H:%M"));
+        return Ok((Schedule::Once(at), meaning));
+    }
+    if let Some(rest) = text.strip_prefix("every ") {
+        let (every, start) = match rest.split_once(" from ") {
+            Some((every, start)) => (every, Some(start)),
+            None => (rest, None),
+        };
+        // "every 90m", but not "every 2 weeks on Friday"
+        if starts_with_digit(every) && !every.contains(char::is_whitespace) {
+            let every = parse_interval(every)?;
+            let start = start.map(parse_datetime).transpose()?;
+            let schedule = Schedule::Every { every, start };
+            let meaning = match start {
+                Some(start) => format!(
+                    "every {} from {}",
+                    format_interval(every),
+                    start.format("%Y-%m-%d %H:%M")
+                ),
+                None => format!("every {} from midnight", format_interval(every)),
+            };
+            return Ok((schedule, meaning));
+        }
+    }
+    let upper = text.to_ascii_uppercase();
+    if ["RRULE:", "FREQ=", "DTSTART:"]
+        .iter()
+        .any(|prefix| upper.starts_with(prefix))
+    {
+        let rule = Rule::from_str(text)?;
+        let meaning = rule.describe();
+        return Ok((Schedule::Rule(Box::new(rule)), meaning));
+    }
+    if text.starts_with(char::is_alphabetic) {
+        let phrase = phrase::parse(text)?;
+        return Ok((phrase.schedule, phrase.meaning));
+    }
+    let cron = cron::Schedule::from_str(text)
+        .map_err(|e| format!("invalid cron expression '{}': {}", text, e))?;
+    let meaning = format!("the cron expression `{}`", text);
+    Ok((Schedule::Cron(Box::new(cron)), meaning))
+}
+
+/// A wall-clock date and time such as `2026-11-03 15:00`.
+pub fn parse_datetime(text: &str) -> Result<NaiveDateTime, String> {
+    let text = text.trim();
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .ok_or_else(|| format!("'{}' is not a date and time like 2026-11-03 15:00", text))
+}
+
+/// An interval such as `90m`, `1h30m`, `2d` or `1w` (units s, m, h, d, w).
+pub fn parse_interval(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("'{}' is not an interval like 90m, 1h30m or 2d", text.trim());
+    let too_long = || format!("'{}' is longer than 1000 weeks", text.trim());
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    for c in text.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        if digits.is_empty() {
+            return Err(invalid());
+        }
+        // Only digits are collected, so parsing fails only on overflow
+        let amount = digits
+            .parse::<u64>()
+            .ok()
+            .and_then(|amount| i32::try_from(amount).ok())
+            .ok_or_else(too_long)?;
+        digits.clear();
+        let unit = match c {
+            's' => Duration::seconds(1),
+            'm' => Duration::minutes(1),
+            'h' => Duration::hours(1),
+            'd' => Duration::days(1),
+            'w' => Duration::weeks(1),
+            _ => return Err(invalid()),
+        };
+        total = unit
+            .checked_mul(amount)
+            .and_then(|part| total.checked_add(&part))
+            .filter(|total| *total <= MAX_INTERVAL)
+            .ok_or_else(too_long)?;
+    }
+    if !digits.is_empty() || total.is_zero() {
+        return Err(invalid());
+    }
+    if total < MIN_INTERVAL {
+        return Err(format!("'{}' is shorter than a minute", text.trim()));
+    }
+    Ok(total)
+}
+
+fn format_interval(interval: Duration) -> String {
+    let mut seconds = interval.num_seconds();
+    let mut text = String::new();
+    for (unit, size) in [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)] {
+        if seconds >= size {
+            text.push_str(&format!("{}{}", seconds / size, unit));
+            seconds %= size;
+        }
+    }
+    text
+}
+
+/// A single fire time of a reminder, in both its own zone and UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurrence {
+    pub local: DateTime<Tz>,
+    pub utc: DateTime<Utc>,
+}
+
+impl Occurrence {
+    pub fn from_utc(utc: DateTime<Utc>, tz: Tz) -> Self {
+        Occurrence {
+            local: utc.with_timezone(&tz),
+            utc,
+        }
+    }
+}
+
+/// Next fire time of `schedule` strictly after `after`, with wall-clock
+/// times evaluated in `tz`. A one-off time has none once it has passed.
+///
+/// DST transitions are resolved as follows:
+/// - a wall-clock time that falls in a skipped hour (spring forward) fires
+///   shifted forward by the length of the gap, e.g. 02:30 becomes 03:30;
+/// - a wall-clock time that occurs twice (fall back) fires once, at the
+///   first of the two instants.
+pub fn next_after(schedule: &Schedule, tz: Tz, after: DateTime<Utc>) -> Option<Occurrence> {
+    let utc = match schedule {
+        Schedule::Cron(cron) => next_cron(cron, tz, after)?,
+        Schedule::Once(at) => Some(resolve_local(tz, *at)).filter(|utc| *utc > after)?,
+        Schedule::Every { every, start } => {
+            let start = start.unwrap_or_default();
+            let anchor = resolve_local(tz, start);
+            if anchor > after {
+                anchor
+            } else {
+                // None once the next step is past the last representable time
+                let every = every.num_seconds();
+                let steps = (after - anchor).num_seconds() / every + 1;
+                steps
+                    .checked_mul(every)
+                    .and_then(Duration::try_seconds)
+                    .and_then(|offset| anchor.checked_add_signed(offset))?
+            }
+        }
+        Schedule::Rule(rule) => {
+            // A day early, so times DST shifts past `after` are still found
+            let from = after.with_timezone(&tz).naive_local() - Duration::days(1);
+            rule.occurrences(from)
+                .map(|local| resolve_local(tz, local))
+                .find(|utc| *utc > after)?
+        }
+    };
+    Some(Occurrence::from_utc(utc, tz))
+}
+
+/// Up to `count` fire times of `schedule` after `after`.
+pub fn upcoming(
+    schedule: &Schedule,
+    tz: Tz,
+    after: DateTime<Utc>,
+    count: usize,
+) -> Vec<Occurrence> {
+    let mut occurrences: Vec<Occurrence> = Vec::with_capacity(count);
+    while occurrences.len() < count {
+        let after = occurrences.last().map_or(after, |last| last.utc);
+        match next_after(schedule, tz, after) {
+            Some(occurrence) => occurrences.push(occurrence),
+            None => break,
+        }
+    }
+    occurrences
+}
+
+fn next_cron(schedule: &cron::Schedule, tz: Tz, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    // The cron crate drops local times that are skipped or ambiguous, so walk
+    // the schedule on a naive wall clock (UTC stands in for "no zone") and
+    // map each candidate into `tz` ourselves.
+    let wall_after = Utc.from_utc_datetime(&after.with_timezone(&tz).naive_local());
+    schedule
+        .after(&wall_after)
+        .map(|wall| resolve_local(tz, wall.naive_utc()))
+        .find(|utc| *utc > after)
+}
+
+/// Map a wall-clock time in `tz` to a single UTC instant.
+pub fn resolve_local(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(dt) => dt.with_timezone(&Utc),
+        LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+        LocalResult::None => {
+            // Inside a gap: apply the offset that was in effect before the
+            // transition, which lands the same distance past the gap's end.
+            let before = local - Duration::hours(24);
+            let offset = tz.offset_from_utc_datetime(&before).fix();
+            Utc.from_utc_datetime(&(local - offset))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(text: &str) -> NaiveDateTime {
+        parse_datetime(text).unwrap()
+    }
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&parse_datetime(text).unwrap())
+    }
+
+    #[test]
+    fn resolves_skipped_and_repeated_local_times() {
+        let tz = chrono_tz::Europe::Berlin;
+        // 2026-03-29 02:00 jumps to 03:00: 02:30 fires as 03:30 CEST
+        assert_eq!(
+            resolve_local(tz, local("2026-03-29 02:30")),
+            utc("2026-03-29 01:30")
+        );
+        // 2026-10-25 03:00 falls back to 02:00: 02:30 fires at the first one
+        assert_eq!(
+            resolve_local(tz, local("2026-10-25 02:30")),
+            utc("2026-10-25 00:30")
+        );
+        assert_eq!(
+            resolve_local(tz, local("2026-10-25 12:00")),
+            utc("2026-10-25 11:00")
+        );
+    }
+
+    #[test]
+    fn cron_fires_once_a_day_across_transitions() {
+        let tz = chrono_tz::Europe::Berlin;
+        let daily = Schedule::from_str("0 30 2 * * *").unwrap();
+        let fires = |after: &str| -> Vec<String> {
+            upcoming(&daily, tz, utc(after), 3)
+                .iter()
+                .map(|o| o.local.format("%m-%d %H:%M %Z").to_string())
+                .collect()
+        };
+        assert_eq!(
+            fires("2026-03-27 12:00"),
+            ["03-28 02:30 CET", "03-29 03:30 CEST", "03-30 02:30 CEST"]
+        );
+        assert_eq!(
+            fires("2026-10-23 12:00"),
+            ["10-24 02:30 CEST", "10-25 02:30 CEST", "10-26 02:30 CET"]
+        );
+    }
+
+    #[test]
+    fn intervals_past_the_end_of_time_never_fire() {
+        let huge = Schedule::Every {
+            every: Duration::days(1_000_000_000),
+            start: None,
+        };
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap();
+        assert_eq!(next_after(&huge, chrono_tz::UTC, now), None);
+        assert!(upcoming(&huge, chrono_tz::UTC, now, 5).is_empty());
+    }
+
+    #[test]
+    fn intervals_are_bounded() {
+        assert_eq!(parse_interval("1h30m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_interval("1000w"), Ok(Duration::weeks(1000)));
+        for text in [
+            "1001w",
+            "999w7d1s",
+            "2147483648m",
+            "4294967356m",
+            "99999999999999999999s",
+        ] {
+            let error = parse_interval(text).unwrap_err();
+            assert!(
+                error.contains("longer than 1000 weeks"),
+                "{}: {}",
+                text,
+                error
+            );
+        }
+        assert!(parse_interval("30s")
+            .unwrap_err()
+            .contains("shorter than a minute"));
+        for text in ["", "m", "1hm", "90", "5x"] {
+            assert!(
+                parse_interval(text)
+                    .unwrap_err()
+                    .contains("is not an interval"),
+                "{}",
+                text
+            );
+        }
+    }
+}
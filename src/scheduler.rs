@@ -0,0 +1,742 @@
+use crate::ack;
+use crate::buttons::{self, Target};
+use crate::calendar;
+use crate::config::{CatchUp, Destination, Reminder, Thread};
+use crate::retry::RetryPolicy;
+use crate::schedule::Occurrence;
+use crate::slack::{SlackClient, SlackError, SlackMessage};
+use crate::state::{PostRef, StateStore};
+use crate::template;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::{self, JoinError, JoinSet};
+use tokio::time::{sleep_until, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Everything a reminder task needs besides its own [`Reminder`].
+pub struct Context {
+    /// One client per workspace, by name.
+    pub clients: HashMap<String, SlackClient>,
+    pub retry: RetryPolicy,
+    pub state: StateStore,
+    /// Cancelled on shutdown; for work that outlives a reminder's task,
+    /// such as escalations.
+    pub shutdown: CancellationToken,
+}
+
+impl Context {
+    /// The client for a reminder's workspace; validation and reload make
+    /// sure it exists.
+    fn client(&self, reminder: &Reminder) -> &SlackClient {
+        &self.clients[&reminder.workspace]
+    }
+}
+
+/// A reminder task currently running in the [`Scheduler`].
+struct Running {
+    reminder: Reminder,
+    cancel: CancellationToken,
+    id: task::Id,
+}
+
+/// What a [`Scheduler::reload`] changed, by reminder name.
+#[derive(Debug, Default)]
+pub struct ReloadSummary {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub rescheduled: Vec<String>,
+    pub unchanged: usize,
+}
+
+/// Owns one `run_schedule` task per reminder. Tasks are tracked in a
+/// `JoinSet` so panics surface, and each gets a child of the shutdown token
+/// so it can be stopped on its own when the config changes.
+pub struct Scheduler {
+    context: Arc<Context>,
+    shutdown: CancellationToken,
+    tasks: JoinSet<()>,
+    running: HashMap<String, Running>,
+    task_names: HashMap<task::Id, String>,
+}
+
+impl Scheduler {
+    pub fn new(context: Arc<Context>, shutdown: CancellationToken) -> Self {
+        Scheduler {
+            context,
+            shutdown,
+            tasks: JoinSet::new(),
+            running: HashMap::new(),
+            task_names: HashMap::new(),
+        }
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Spawn the task for `reminder`. `catch_up` runs the reminder's catch-up
+    /// policy first; it is off for reloads so a new schedule does not count
+    /// its own past occurrences as missed.
+    pub fn start(&mut self, reminder: Reminder, catch_up: bool) {
+        println!(
+            "Scheduling reminder '{}' ({} {}) in workspace '{}' for {}",
+            reminder.name,
+            reminder.schedule,
+            reminder.timezone,
+            reminder.workspace,
+            reminder
+                .destinations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let cancel = self.shutdown.child_token();
+        let handle = self.tasks.spawn(run_schedule(
+            reminder.clone(),
+            Arc::clone(&self.context),
+            cancel.clone(),
+            catch_up,
+        ));
+        self.task_names.insert(handle.id(), reminder.name.clone());
+        self.running.insert(
+            reminder.name.clone(),
+            Running {
+                reminder,
+                cancel,
+                id: handle.id(),
+            },
+        );
+    }
+
+    /// Stop the task for `name`. An in-flight send is allowed to finish.
+    pub fn stop(&mut self, name: &str) {
+        if let Some(running) = self.running.remove(name) {
+            println!("Unscheduling reminder '{}'", name);
+            running.cancel.cancel();
+        }
+    }
+
+    /// Bring the running tasks in line with `reminders`: start new ones,
+    /// stop removed ones and restart the ones whose definition changed.
+    pub fn reload(&mut self, reminders: Vec<Reminder>) -> ReloadSummary {
+        let mut summary = ReloadSummary::default();
+        let mut incoming: HashMap<String, Reminder> = reminders
+            .into_iter()
+            .map(|reminder| (reminder.name.clone(), reminder))
+            .collect();
+
+        let mut names: Vec<String> = self.running.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            match incoming.remove(&name) {
+                None => {
+                    self.stop(&name);
+                    summary.removed.push(name);
+                }
+                Some(reminder) if reminder != self.running[&name].reminder => {
+                    self.stop(&name);
+                    self.start(reminder, false);
+                    summary.rescheduled.push(name);
+                }
+                Some(_) => summary.unchanged += 1,
+            }
+        }
+
+        let mut added: Vec<Reminder> = incoming.into_values().collect();
+        added.sort_by(|a, b| a.name.cmp(&b.name));
+        for reminder in added {
+            summary.added.push(reminder.name.clone());
+            self.start(reminder, false);
+        }
+        summary
+    }
+
+    /// Wait for the next task to end and log how it ended. Returns `None`
+    /// when no tasks are left, otherwise `Some(false)` if it panicked.
+    pub async fn join_next(&mut self) -> Option<bool> {
+        let result = self.tasks.join_next_with_id().await?;
+        let id = match &result {
+            Ok((id, ())) => *id,
+            Err(e) => e.id(),
+        };
+        self.running.retain(|_, running| running.id != id);
+        Some(report_task_exit(result, &mut self.task_names))
+    }
+
+    /// Stop every task and wait up to `timeout` for in-flight sends. Returns
+    /// `Err` with the number of tasks aborted at the deadline, otherwise
+    /// whether every task ended cleanly.
+    pub async fn shutdown(&mut self, timeout: Duration) -> Result<bool, usize> {
+        self.shutdown.cancel();
+        self.running.clear();
+        let drain = async {
+            let mut clean = true;
+            while let Some(ok) = self.join_next().await {
+                clean &= ok;
+            }
+            clean
+        };
+        match tokio::time::timeout(timeout, drain).await {
+            Ok(clean) => Ok(clean),
+            Err(_) => {
+                let remaining = self.tasks.len();
+                self.tasks.abort_all();
+                Err(remaining)
+            }
+        }
+    }
+}
+
+async fn run_schedule(
+    reminder: Reminder,
+    context: Arc<Context>,
+    shutdown: CancellationToken,
+    run_catch_up: bool,
+) {
+    if run_catch_up {
+        catch_up(&reminder, &context, &shutdown).await;
+    }
+
+    let mut after = Utc::now();
+    loop {
+        if let Some(reason) = ended(&reminder, &context.state) {
+            finish(&reminder, &context, &reason).await;
+            break;
+        }
+        if let Some(planned) = calendar::next_fire(&reminder, after) {
+            for (skipped, reason) in &planned.skipped {
+                println!(
+                    "Skipping '{}' scheduled at {}: {}",
+                    reminder.name, skipped.local, reason
+                );
+            }
+            let occurrence = planned.occurrence;
+            if let Some((scheduled, reason)) = &planned.moved_from {
+                println!(
+                    "Moving '{}' from {} to {}: {}",
+                    reminder.name, scheduled.local, occurrence.local, reason
+                );
+            }
+            after = occurrence.utc;
+            let now = Utc::now();
+            let duration = occurrence.utc - now;
+            let duration_std = match duration.to_std() {
+                Ok(d) => d,
+                Err(_) => {
+                    eprintln!("Scheduled time is in the past. Skipping.");
+                    continue;
+                }
+            };
+            let delay = reminder.jitter.map_or(Duration::ZERO, |jitter| {
+                jitter.delay(&reminder.name, occurrence.utc)
+            });
+            let instant = Instant::now() + duration_std + delay;
+
+            if delay.is_zero() {
+                println!(
+                    "Next '{}' reminder scheduled at {} ({})",
+                    reminder.name, occurrence.local, occurrence.utc
+                );
+            } else {
+                println!(
+                    "Next '{}' reminder scheduled at {} ({}), posting {}s later to spread load",
+                    reminder.name,
+                    occurrence.local,
+                    occurrence.utc,
+                    delay.as_secs()
+                );
+            }
+
+            tokio::select! {
+                _ = sleep_until(instant) => {}
+                _ = shutdown.cancelled() => {
+                    println!("Stopping reminder '{}'", reminder.name);
+                    break;
+                }
+            }
+
+            deliver(&reminder, &occurrence, &context).await;
+        } else if reminder.schedule.is_one_off() || reminder.ends_at.is_some() {
+            finish(&reminder, &context, "it has no occurrences left").await;
+            break;
+        } else {
+            eprintln!("No upcoming schedule found. Exiting task.");
+            break;
+        }
+    }
+}
+
+/// Why `reminder` will not post again, if it will not: its
+/// `max_occurrences` have been posted, its `ends_at` has passed or its
+/// one-off time has fired.
+pub fn ended(reminder: &Reminder, state: &StateStore) -> Option<String> {
+    if let Some(max) = reminder.max_occurrences {
+        if state.fire_count(&reminder.name) >= max {
+            return Some(format!("all {} occurrence(s) have been posted", max));
+        }
+    }
+    if let Some(ends_at) = reminder.ends_at.filter(|ends_at| *ends_at <= Utc::now()) {
+        return Some(format!(
+            "its end time {} has passed",
+            ends_at.with_timezone(&reminder.timezone)
+        ));
+    }
+    let fired = reminder.schedule.is_one_off()
+        && state
+            .last_fired(&reminder.name)
+            .is_some_and(|last| calendar::next_fire(reminder, last).is_none());
+    fired.then(|| "its one-off time has fired".to_string())
+}
+
+/// Deactivate a reminder that has ended: one created from Slack is deleted,
+/// one from the config file stays out of the active set (see
+/// `commands::active`) until its limits are changed.
+async fn finish(reminder: &Reminder, context: &Context, reason: &str) {
+    match context.state.remove_stored(&reminder.name).await {
+        Ok(true) => println!(
+            "Reminder '{}' has ended ({}); removed it",
+            reminder.name, reason
+        ),
+        Ok(false) => println!(
+            "Reminder '{}' has ended ({}); deactivated it",
+            reminder.name, reason
+        ),
+        Err(e) => eprintln!("Failed to remove ended reminder '{}': {}", reminder.name, e),
+    }
+}
+
+/// Post occurrences missed since the last recorded fire, per the reminder's
+/// catch-up policy.
+async fn catch_up(reminder: &Reminder, context: &Arc<Context>, shutdown: &CancellationToken) {
+    let Some(last_fired) = context.state.last_fired(&reminder.name) else {
+        return;
+    };
+    let fire_count = context.state.fire_count(&reminder.name);
+    let (missed, to_send) = select_catch_up(reminder, last_fired, fire_count, Utc::now());
+    if missed == 0 {
+        return;
+    }
+    println!(
+        "Reminder '{}' missed {} occurrence(s) within its grace window (last fired {}); catch-up policy {:?} posts {}",
+        reminder.name,
+        missed,
+        last_fired,
+        reminder.catch_up,
+        to_send.len()
+    );
+    for occurrence in &to_send {
+        if shutdown.is_cancelled() {
+            return;
+        }
+        println!(
+            "Catching up '{}' occurrence scheduled at {} ({})",
+            reminder.name, occurrence.local, occurrence.utc
+        );
+        deliver(reminder, occurrence, context).await;
+    }
+}
+
+/// Count the occurrences after `last_fired` and up to `now` (within the
+/// grace window) and pick those the catch-up policy posts, never taking the
+/// reminder past `max_occurrences`.
+fn select_catch_up(
+    reminder: &Reminder,
+    last_fired: DateTime<Utc>,
+    fire_count: u64,
+    now: DateTime<Utc>,
+) -> (usize, Vec<Occurrence>) {
+    let mut missed = Vec::new();
+    let mut after = now
+        .checked_sub_signed(reminder.catch_up_grace)
+        .map_or(last_fired, |start| last_fired.max(start));
+    while let Some(occurrence) =
+        calendar::next_fire(reminder, after).map(|planned| planned.occurrence)
+    {
+        if occurrence.utc > now {
+            break;
+        }
+        after = occurrence.utc;
+        missed.push(occurrence);
+    }
+
+    let mut to_send = match reminder.catch_up {
+        CatchUp::Skip => Vec::new(),
+        CatchUp::Once => missed.last().copied().into_iter().collect(),
+        CatchUp::All => missed.clone(),
+    };
+    if let Some(max) = reminder.max_occurrences {
+        let left = max.saturating_sub(fire_count);
+        to_send.truncate(left.try_into().unwrap_or(usize::MAX));
+    }
+    (missed.len(), to_send)
+}
+
+/// Send one occurrence of a reminder to each of its destinations. Each
+/// conversation is claimed in the delivery ledger before posting and settled
+/// afterwards, on its own so one failure does not block the others; the
+/// state store's last fire is updated if any post succeeded. Reminders that
+/// need acknowledgement start waiting for it.
+async fn deliver(reminder: &Reminder, occurrence: &Occurrence, context: &Arc<Context>) {
+    // Fill in the templates for this occurrence
+    let next = calendar::next_fire(reminder, occurrence.utc).map(|planned| planned.occurrence);
+    let vars = template::variables(
+        &reminder.name,
+        occurrence,
+        context.state.fire_count(&reminder.name) + 1,
+        next.as_ref(),
+        &reminder.vars,
+    );
+    let text = template::render_str(&reminder.text, &vars);
+    let blocks = reminder
+        .blocks
+        .as_ref()
+        .map(|blocks| template::render_value(blocks, &vars));
+    let blocks = if reminder.buttons || reminder.ack.is_some() {
+        let target = Target {
+            reminder: reminder.name.clone(),
+            workspace: reminder.workspace.clone(),
+            timezone: reminder.timezone.name().to_string(),
+        };
+        Some(buttons::attach(blocks.as_ref(), &text, &target))
+    } else {
+        blocks
+    };
+    let attachments = reminder
+        .attachments
+        .as_ref()
+        .map(|attachments| template::render_value(attachments, &vars));
+
+    let mut attempted = 0;
+    let mut delivered = Vec::new();
+    for destination in &reminder.destinations {
+        for (key, channel) in resolve(reminder, destination, context).await {
+            match context
+                .state
+                .begin_delivery(&reminder.name, occurrence.utc, &key)
+                .await
+            {
+                Ok(None) => {}
+                Ok(Some(existing)) => {
+                    println!(
+                        "Occurrence of '{}' at {} to {} is already {:?} in the ledger. Skipping.",
+                        reminder.name, occurrence.utc, key, existing.status
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    // Without a durable claim a crash could lead to a double post.
+                    eprintln!(
+                        "Failed to record delivery of '{}' to {} in the ledger, not sending: {}",
+                        reminder.name, key, e
+                    );
+                    continue;
+                }
+            }
+            attempted += 1;
+
+            let outcome = match channel {
+                Ok(channel) => {
+                    let thread_ts = reminder
+                        .thread
+                        .as_ref()
+                        .and_then(|thread| thread_parent(reminder, thread, &key, context));
+                    let message = SlackMessage {
+                        channel: &channel,
+                        text: &text,
+                        blocks: blocks.as_ref(),
+                        attachments: attachments.as_ref(),
+                        thread_ts: thread_ts.as_deref(),
+                        reply_broadcast: reminder
+                            .thread
+                            .as_ref()
+                            .filter(|_| thread_ts.is_some())
+                            .map(|thread| thread.broadcast),
+                    };
+                    post(reminder, occurrence, &key, &message, context).await
+                }
+                Err(e) => {
+                    eprintln!("Failed to resolve {} for '{}': {}", key, reminder.name, e);
+                    Err(e.to_string())
+                }
+            };
+            if let Ok((Some(channel), Some(ts))) = &outcome {
+                delivered.push(PostRef {
+                    channel: channel.clone(),
+                    ts: ts.clone(),
+                    thread_ts: None,
+                    posted_at: Utc::now(),
+                });
+            }
+
+            if let Err(e) = context
+                .state
+                .finish_delivery(&reminder.name, occurrence.utc, &key, outcome)
+                .await
+            {
+                eprintln!(
+                    "Failed to settle delivery of '{}' to {} in the ledger: {}",
+                    reminder.name, key, e
+                );
+            }
+        }
+    }
+
+    if attempted > 0 {
+        println!(
+            "Occurrence of '{}' at {} delivered to {}/{} destination(s)",
+            reminder.name,
+            occurrence.utc,
+            delivered.len(),
+            attempted
+        );
+    }
+    if let Some(ack) = &reminder.ack {
+        if !delivered.is_empty() {
+            ack::track(reminder, ack, &text, blocks.as_ref(), delivered, context).await;
+        }
+    }
+}
+
+/// Turn a destination into the conversations to post in, each with its
+/// ledger key: a channel as-is, a user's DM, or a DM per user group member.
+async fn resolve(
+    reminder: &Reminder,
+    destination: &Destination,
+    context: &Context,
+) -> Vec<(String, Result<String, SlackError>)> {
+    let label = format!("{} to {}", reminder.name, destination.key());
+    match destination {
+        Destination::Channel(id) => vec![(destination.key(), Ok(id.clone()))],
+        Destination::User(user) => {
+            let channel = context
+                .retry
+                .run(&label, || context.client(reminder).open_dm(user))
+                .await;
+            vec![(destination.key(), channel)]
+        }
+        Destination::UserGroup(group) => {
+            let members = match context
+                .retry
+                .run(&label, || context.client(reminder).usergroup_members(group))
+                .await
+            {
+                Ok(members) => members,
+                Err(e) => return vec![(destination.key(), Err(e))],
+            };
+            if members.is_empty() {
+                println!("User group {} of '{}' has no members", group, reminder.name);
+            }
+            let mut targets = Vec::new();
+            for user in members {
+                let label = format!("{} to {}:{}", reminder.name, destination.key(), user);
+                let channel = context
+                    .retry
+                    .run(&label, || context.client(reminder).open_dm(&user))
+                    .await;
+                targets.push((format!("{}:{}", destination.key(), user), channel));
+            }
+            targets
+        }
+    }
+}
+
+/// Post to one conversation and record where the post landed.
+async fn post(
+    reminder: &Reminder,
+    occurrence: &Occurrence,
+    key: &str,
+    message: &SlackMessage<'_>,
+    context: &Context,
+) -> Result<(Option<String>, Option<String>), String> {
+    let label = format!("{} to {}", reminder.name, key);
+    match context
+        .retry
+        .run(&label, || context.client(reminder).post_message(message))
+        .await
+    {
+        Ok(response) => {
+            if let Some(warning) = &response.warning {
+                eprintln!(
+                    "Slack warning for '{}' to {}: {}",
+                    reminder.name, key, warning
+                );
+            }
+            println!(
+                "Message for '{}' sent to {} at {} (channel {}, ts {})",
+                reminder.name,
+                key,
+                Utc::now(),
+                response.data.channel.as_deref().unwrap_or("?"),
+                response.data.ts.as_deref().unwrap_or("?")
+            );
+            if let Err(e) = context
+                .state
+                .record_fired(&reminder.name, occurrence.utc)
+                .await
+            {
+                eprintln!("Failed to record fire of '{}': {}", reminder.name, e);
+            }
+            if let (Some(channel), Some(ts)) = (&response.data.channel, &response.data.ts) {
+                let post = PostRef {
+                    channel: channel.clone(),
+                    ts: ts.clone(),
+                    thread_ts: message.thread_ts.map(str::to_string),
+                    posted_at: Utc::now(),
+                };
+                if let Err(e) = context.state.record_post(&reminder.name, key, post).await {
+                    eprintln!(
+                        "Failed to record post of '{}' to {}: {}",
+                        reminder.name, key, e
+                    );
+                }
+            }
+            Ok((response.data.channel, response.data.ts))
+        }
+        Err(e) => {
+            eprintln!(
+                "Failed to send message for '{}' to {}: {}",
+                reminder.name, key, e
+            );
+            Err(e.to_string())
+        }
+    }
+}
+
+/// The `ts` to reply under, if the parent reminder has a recent enough post
+/// to the same destination.
+fn thread_parent(
+    reminder: &Reminder,
+    thread: &Thread,
+    key: &str,
+    context: &Context,
+) -> Option<String> {
+    let Some(parent) = context.state.last_post(&thread.parent, key) else {
+        println!(
+            "No earlier post of '{}' to {} to thread '{}' under, posting top-level",
+            thread.parent, key, reminder.name
+        );
+        return None;
+    };
+    if let Some(max_age) = thread.max_age {
+        if Utc::now() - parent.posted_at > max_age {
+            println!(
+                "Latest post of '{}' to {} is older than {}h; posting '{}' top-level",
+                thread.parent,
+                key,
+                max_age.num_hours(),
+                reminder.name
+            );
+            return None;
+        }
+    }
+    println!(
+        "Threading '{}' under '{}' in {} (ts {})",
+        reminder.name,
+        thread.parent,
+        key,
+        parent.thread_root()
+    );
+    Some(parent.thread_root().to_string())
+}
+
+/// Log how a reminder task ended. Returns `false` if it panicked.
+fn report_task_exit(
+    result: Result<(task::Id, ()), JoinError>,
+    names: &mut HashMap<task::Id, String>,
+) -> bool {
+    match result {
+        Ok((id, ())) => {
+            let name = names.remove(&id).unwrap_or_default();
+            println!("Reminder task '{}' finished", name);
+            true
+        }
+        Err(e) => {
+            let name = names.remove(&e.id()).unwrap_or_default();
+            if e.is_panic() {
+                eprintln!("Reminder task '{}' panicked: {}", name, e);
+                false
+            } else {
+                eprintln!("Reminder task '{}' was cancelled", name);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::Schedule;
+    use chrono::{TimeZone, Timelike};
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    fn reminder(catch_up: CatchUp, max_occurrences: Option<u64>) -> Reminder {
+        Reminder {
+            name: "hourly".to_string(),
+            workspace: "default".to_string(),
+            schedule: Schedule::from_str("0 0 * * * *").unwrap(),
+            destinations: Vec::new(),
+            text: String::new(),
+            timezone: chrono_tz::UTC,
+            catch_up,
+            catch_up_grace: chrono::Duration::hours(24),
+            blocks: None,
+            attachments: None,
+            vars: BTreeMap::new(),
+            thread: None,
+            buttons: false,
+            ack: None,
+            blackout: None,
+            starts_at: None,
+            ends_at: None,
+            max_occurrences,
+            jitter: None,
+        }
+    }
+
+    #[test]
+    fn catch_up_posts_each_missed_occurrence_once() {
+        let at = |hour, minute| Utc.with_ymd_and_hms(2026, 10, 15, hour, minute, 0).unwrap();
+        let hours = |occurrences: &[Occurrence]| -> Vec<u32> {
+            occurrences.iter().map(|o| o.utc.hour()).collect()
+        };
+        // Last fired at 09:00; down until 12:30 missed 10:00, 11:00 and 12:00
+        let last_fired = at(9, 0);
+        let now = at(12, 30);
+
+        let (missed, all) = select_catch_up(&reminder(CatchUp::All, None), last_fired, 1, now);
+        assert_eq!(missed, 3);
+        assert_eq!(hours(&all), [10, 11, 12]);
+
+        let (_, once) = select_catch_up(&reminder(CatchUp::Once, None), last_fired, 1, now);
+        assert_eq!(hours(&once), [12]);
+        let (_, skip) = select_catch_up(&reminder(CatchUp::Skip, None), last_fired, 1, now);
+        assert!(skip.is_empty());
+
+        // Once 12:00 has been posted, a second restart finds nothing to send
+        let (missed, again) = select_catch_up(&reminder(CatchUp::All, None), at(12, 0), 4, now);
+        assert_eq!(missed, 0);
+        assert!(again.is_empty());
+
+        // Restarting exactly on an occurrence sends it, but only once
+        let (_, on_time) = select_catch_up(&reminder(CatchUp::All, None), at(11, 0), 3, at(12, 0));
+        assert_eq!(hours(&on_time), [12]);
+
+        // The grace window and max_occurrences both limit what is posted
+        let mut short = reminder(CatchUp::All, None);
+        short.catch_up_grace = chrono::Duration::minutes(150);
+        let (missed, recent) = select_catch_up(&short, last_fired, 1, now);
+        assert_eq!((missed, hours(&recent)), (2, vec![11, 12]));
+        short.catch_up_grace = chrono::Duration::max_value();
+        let (missed, _) = select_catch_up(&short, last_fired, 1, now);
+        assert_eq!(missed, 3);
+        let (_, capped) = select_catch_up(&reminder(CatchUp::All, Some(3)), last_fired, 1, now);
+        assert_eq!(hours(&capped), [10, 11]);
+        let (_, done) = select_catch_up(&reminder(CatchUp::All, Some(3)), last_fired, 3, now);
+        assert!(done.is_empty());
+    }
+}
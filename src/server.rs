@@ -0,0 +1,198 @@
+use crate::commands::{self, CommandRequest, SlashCommand};
+use crate::events::{self, Event};
+use crate::signature::{self, Verifier};
+use chrono::Utc;
+use hyper::body::HttpBody;
+use hyper::header::CONTENT_LENGTH;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Path Slack posts slash commands to.
+pub const COMMANDS_PATH: &str = "/slack/commands";
+/// Path Slack posts interactive payloads (button presses) to.
+pub const INTERACTIONS_PATH: &str = "/slack/interactions";
+/// Path the Events API posts to (reactions).
+pub const EVENTS_PATH: &str = "/slack/events";
+
+/// Largest request body accepted. Slack's payloads are a few KiB; anything
+/// bigger is refused before it is read, let alone verified.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Form body of an interactive request: the payload is JSON in one field.
+#[derive(Deserialize)]
+struct InteractionForm {
+    payload: String,
+}
+
+/// Bind `addr` and serve slash commands, button presses and events until
+/// `shutdown` is cancelled. Binding happens before this returns so a taken
+/// port fails at startup.
+pub fn spawn(
+    addr: SocketAddr,
+    verifier: Verifier,
+    requests: mpsc::Sender<CommandRequest>,
+    events: mpsc::Sender<Event>,
+    shutdown: CancellationToken,
+) -> Result<(), hyper::Error> {
+    let builder = Server::try_bind(&addr)?;
+    let verifier = Arc::new(verifier);
+    let make_service = make_service_fn(move |_| {
+        let verifier = Arc::clone(&verifier);
+        let requests = requests.clone();
+        let events = events.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle(
+                    request,
+                    Arc::clone(&verifier),
+                    requests.clone(),
+                    events.clone(),
+                )
+            }))
+        }
+    });
+    let server = builder
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown.cancelled_owned());
+    println!(
+        "Listening for /remind-bot on http://{}{}, button presses on {} and events on {}",
+        addr, COMMANDS_PATH, INTERACTIONS_PATH, EVENTS_PATH
+    );
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            eprintln!("Slash command server failed: {}", e);
+        }
+    });
+    Ok(())
+}
+
+async fn handle(
+    request: Request<Body>,
+    verifier: Arc<Verifier>,
+    requests: mpsc::Sender<CommandRequest>,
+    events: mpsc::Sender<Event>,
+) -> Result<Response<Body>, Infallible> {
+    let path = request.uri().path().to_string();
+    let known = [COMMANDS_PATH, INTERACTIONS_PATH, EVENTS_PATH].contains(&path.as_str());
+    if request.method() != Method::POST || !known {
+        return Ok(status(StatusCode::NOT_FOUND));
+    }
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let timestamp = header(signature::TIMESTAMP_HEADER);
+    let signature = header(signature::SIGNATURE_HEADER);
+    let declared = header(CONTENT_LENGTH.as_str()).and_then(|length| length.parse::<u64>().ok());
+    if declared.is_some_and(|length| length > MAX_BODY_BYTES as u64) {
+        return Ok(status(StatusCode::PAYLOAD_TOO_LARGE));
+    }
+    let body = match read_body(request.into_body()).await {
+        Ok(body) => body,
+        Err(code) => return Ok(status(code)),
+    };
+
+    let verified = verifier.verify(
+        timestamp.as_deref(),
+        signature.as_deref(),
+        &body,
+        Utc::now().timestamp(),
+    );
+    if let Err(e) = verified {
+        eprintln!("Rejected request to {}: {}", path, e);
+        return Ok(status(StatusCode::UNAUTHORIZED));
+    }
+
+    match path.as_str() {
+        INTERACTIONS_PATH => return Ok(interaction(&body, &events).await),
+        EVENTS_PATH => return Ok(event_callback(&body, &events).await),
+        _ => {}
+    }
+
+    let form: SlashCommand = match serde_urlencoded::from_bytes(&body) {
+        Ok(form) => form,
+        Err(e) => {
+            eprintln!("Malformed slash command request: {}", e);
+            return Ok(status(StatusCode::BAD_REQUEST));
+        }
+    };
+    let text = commands::dispatch(form, &requests).await;
+    Ok(ephemeral(&text))
+}
+
+/// Collect the body, giving up once it passes `MAX_BODY_BYTES` whatever
+/// Content-Length said.
+async fn read_body(mut body: Body) -> Result<Vec<u8>, StatusCode> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if bytes.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// Hand a button press on and acknowledge it; Slack only needs a 200.
+async fn interaction(body: &[u8], events: &mpsc::Sender<Event>) -> Response<Body> {
+    let payload = serde_urlencoded::from_bytes::<InteractionForm>(body)
+        .map_err(|e| e.to_string())
+        .and_then(|form| serde_json::from_str(&form.payload).map_err(|e| e.to_string()));
+    match payload {
+        Ok(payload) => {
+            if let Some(event) = events::from_interaction(&payload) {
+                let _ = events.send(event).await;
+            }
+            status(StatusCode::OK)
+        }
+        Err(e) => {
+            eprintln!("Malformed interaction request: {}", e);
+            status(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// Answer the Events API's URL verification, or hand an event on and
+/// acknowledge it.
+async fn event_callback(body: &[u8], events: &mpsc::Sender<Event>) -> Response<Body> {
+    let payload: serde_json::Value = match serde_json::from_slice(body) {
+        Ok(payload) => payload,
+        Err(e) => {
+            eprintln!("Malformed event request: {}", e);
+            return status(StatusCode::BAD_REQUEST);
+        }
+    };
+    if payload["type"] == "url_verification" {
+        let challenge = payload["challenge"].as_str().unwrap_or_default();
+        return Response::new(Body::from(challenge.to_string()));
+    }
+    if let Some(event) = events::from_callback(&payload) {
+        let _ = events.send(event).await;
+    }
+    status(StatusCode::OK)
+}
+
+fn status(code: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = code;
+    response
+}
+
+/// A reply only the invoking user sees.
+fn ephemeral(text: &str) -> Response<Body> {
+    let body = serde_json::json!({ "response_type": "ephemeral", "text": text });
+    Response::builder()
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("static response parts are valid")
+}
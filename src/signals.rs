@@ -0,0 +1,53 @@
+/// A process signal the bot reacts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalEvent {
+    /// SIGTERM or SIGINT: stop scheduling and exit.
+    Shutdown(&'static str),
+    /// SIGHUP: reload the config file.
+    Reload,
+}
+
+/// Signal streams, installed once so no signal is missed between polls.
+#[cfg(unix)]
+pub struct Signals {
+    terminate: tokio::signal::unix::Signal,
+    interrupt: tokio::signal::unix::Signal,
+    hangup: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    pub fn install() -> std::io::Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        Ok(Signals {
+            terminate: signal(SignalKind::terminate())?,
+            interrupt: signal(SignalKind::interrupt())?,
+            hangup: signal(SignalKind::hangup())?,
+        })
+    }
+
+    pub async fn recv(&mut self) -> SignalEvent {
+        tokio::select! {
+            _ = self.terminate.recv() => SignalEvent::Shutdown("SIGTERM"),
+            _ = self.interrupt.recv() => SignalEvent::Shutdown("SIGINT"),
+            _ = self.hangup.recv() => SignalEvent::Reload,
+        }
+    }
+}
+
+/// Only Ctrl-C is available off Unix; there is no reload signal.
+#[cfg(not(unix))]
+pub struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    pub fn install() -> std::io::Result<Self> {
+        Ok(Signals)
+    }
+
+    pub async fn recv(&mut self) -> SignalEvent {
+        let _ = tokio::signal::ctrl_c().await;
+        SignalEvent::Shutdown("Ctrl-C")
+    }
+}
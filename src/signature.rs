@@ -0,0 +1,221 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt;
+use std::time::Duration;
+
+/// Slack's recommended window for `X-Slack-Request-Timestamp`.
+pub const DEFAULT_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+
+pub const TIMESTAMP_HEADER: &str = "X-Slack-Request-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Slack-Signature";
+
+#[derive(Debug, PartialEq)]
+pub enum SignatureError {
+    /// `X-Slack-Request-Timestamp` or `X-Slack-Signature` is absent.
+    Missing(&'static str),
+    /// The timestamp is not a Unix time in seconds.
+    BadTimestamp,
+    /// The timestamp is further from the current time than the tolerance.
+    Stale { skew_secs: i64 },
+    /// The signature is not `v0=<hex>`.
+    Malformed,
+    /// The signature matches none of the signing secrets.
+    Mismatch,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::Missing(header) => write!(f, "missing {} header", header),
+            SignatureError::BadTimestamp => write!(f, "request timestamp is not a number"),
+            SignatureError::Stale { skew_secs } => {
+                write!(f, "request timestamp is {}s away from now", skew_secs)
+            }
+            SignatureError::Malformed => write!(f, "signature is not in v0=<hex> form"),
+            SignatureError::Mismatch => write!(f, "signature does not match"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Verifies Slack request signatures (`v0=` HMAC-SHA256 over
+/// `v0:{timestamp}:{body}`) for any inbound integration. During a secret
+/// rotation both the current and the previous secret are accepted.
+#[derive(Clone)]
+pub struct Verifier {
+    current: String,
+    previous: Option<String>,
+    tolerance: Duration,
+}
+
+impl Verifier {
+    pub fn new(current: String) -> Self {
+        Verifier {
+            current,
+            previous: None,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Also accept requests signed with the secret being rotated out.
+    pub fn with_previous(mut self, previous: Option<String>) -> Self {
+        self.previous = previous;
+        self
+    }
+
+    /// How far the request timestamp may be from now, either way.
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Check a request given its timestamp and signature headers, raw body
+    /// and the current Unix time.
+    pub fn verify(
+        &self,
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+        now: i64,
+    ) -> Result<(), SignatureError> {
+        let timestamp = timestamp.ok_or(SignatureError::Missing(TIMESTAMP_HEADER))?;
+        let signature = signature.ok_or(SignatureError::Missing(SIGNATURE_HEADER))?;
+
+        let sent_at: i64 = timestamp
+            .trim()
+            .parse()
+            .map_err(|_| SignatureError::BadTimestamp)?;
+        let skew_secs = now.saturating_sub(sent_at);
+        if skew_secs.unsigned_abs() > self.tolerance.as_secs() {
+            return Err(SignatureError::Stale { skew_secs });
+        }
+
+        let expected = signature
+            .strip_prefix("v0=")
+            .and_then(|hex| hex::decode(hex).ok())
+            .ok_or(SignatureError::Malformed)?;
+        let matches = |secret: &str| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+                .expect("HMAC accepts keys of any length");
+            mac.update(b"v0:");
+            mac.update(timestamp.as_bytes());
+            mac.update(b":");
+            mac.update(body);
+            // Constant-time comparison
+            mac.verify_slice(&expected).is_ok()
+        };
+        if matches(&self.current) || self.previous.as_deref().is_some_and(matches) {
+            Ok(())
+        } else {
+            Err(SignatureError::Mismatch)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The example from https://api.slack.com/authentication/verifying-requests-from-slack
+    const SECRET: &str = "8f742231b10e8888abcd99yyyzzz85a5";
+    const TIMESTAMP: &str = "1531420618";
+    const BODY: &str = "token=xyzz0WbapA4vBCDEFasx0q6G&team_id=T1DC2JH3J&team_domain=testteamnow&channel_id=G8PSS9T3V&channel_name=foobar&user_id=U2CERLKJA&user_name=roadrunner&command=%2Fwebhook-collect&text=&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT1DC2JH3J%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&trigger_id=398738663015.47445629121.803a0bc887a14d10d2c447fce8b6703c";
+    const SIGNATURE: &str = "v0=a2114d57b48eac39b9ad189dd8316235a7b4a8d21a10bd27519666489c69b503";
+    const NOW: i64 = 1531420618;
+
+    fn check(
+        verifier: &Verifier,
+        body: &str,
+        signature: &str,
+        now: i64,
+    ) -> Result<(), SignatureError> {
+        verifier.verify(Some(TIMESTAMP), Some(signature), body.as_bytes(), now)
+    }
+
+    #[test]
+    fn accepts_slack_example() {
+        let verifier = Verifier::new(SECRET.to_string());
+        assert_eq!(check(&verifier, BODY, SIGNATURE, NOW), Ok(()));
+    }
+
+    #[test]
+    fn rejects_tampered_body() {
+        let verifier = Verifier::new(SECRET.to_string());
+        let body = BODY.replace("roadrunner", "coyote");
+        assert_eq!(
+            check(&verifier, &body, SIGNATURE, NOW),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_secret() {
+        let verifier = Verifier::new("not-the-secret".to_string());
+        assert_eq!(
+            check(&verifier, BODY, SIGNATURE, NOW),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn accepts_previous_secret_during_rotation() {
+        let verifier =
+            Verifier::new("new-secret".to_string()).with_previous(Some(SECRET.to_string()));
+        assert_eq!(check(&verifier, BODY, SIGNATURE, NOW), Ok(()));
+
+        let verifier =
+            Verifier::new(SECRET.to_string()).with_previous(Some("old-secret".to_string()));
+        assert_eq!(check(&verifier, BODY, SIGNATURE, NOW), Ok(()));
+    }
+
+    #[test]
+    fn enforces_tolerance_both_ways() {
+        let verifier = Verifier::new(SECRET.to_string()).with_tolerance(Duration::from_secs(60));
+        assert_eq!(check(&verifier, BODY, SIGNATURE, NOW + 60), Ok(()));
+        assert_eq!(check(&verifier, BODY, SIGNATURE, NOW - 60), Ok(()));
+        assert_eq!(
+            check(&verifier, BODY, SIGNATURE, NOW + 61),
+            Err(SignatureError::Stale { skew_secs: 61 })
+        );
+        assert_eq!(
+            check(&verifier, BODY, SIGNATURE, NOW - 61),
+            Err(SignatureError::Stale { skew_secs: -61 })
+        );
+    }
+
+    #[test]
+    fn default_tolerance_is_five_minutes() {
+        let verifier = Verifier::new(SECRET.to_string());
+        assert_eq!(check(&verifier, BODY, SIGNATURE, NOW + 300), Ok(()));
+        assert!(matches!(
+            check(&verifier, BODY, SIGNATURE, NOW + 301),
+            Err(SignatureError::Stale { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_missing_and_malformed_headers() {
+        let verifier = Verifier::new(SECRET.to_string());
+        assert_eq!(
+            verifier.verify(None, Some(SIGNATURE), BODY.as_bytes(), NOW),
+            Err(SignatureError::Missing(TIMESTAMP_HEADER))
+        );
+        assert_eq!(
+            verifier.verify(Some(TIMESTAMP), None, BODY.as_bytes(), NOW),
+            Err(SignatureError::Missing(SIGNATURE_HEADER))
+        );
+        assert_eq!(
+            verifier.verify(Some("yesterday"), Some(SIGNATURE), BODY.as_bytes(), NOW),
+            Err(SignatureError::BadTimestamp)
+        );
+        assert_eq!(
+            check(&verifier, BODY, "a2114d57b48eac39b9ad189dd8316235", NOW),
+            Err(SignatureError::Malformed)
+        );
+        assert_eq!(
+            check(&verifier, BODY, "v0=not-hex", NOW),
+            Err(SignatureError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,441 @@
+use crate::ratelimit::{MethodLimits, RateLimiter};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+const API_BASE: &str = "https://slack.com/api";
+
+#[derive(Serialize)]
+pub struct SlackMessage<'a> {
+    pub channel: &'a str,
+    /// Plain-text fallback, also used for notifications when `blocks` is set.
+    pub text: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<&'a serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<&'a serde_json::Value>,
+    /// Post as a reply in the thread rooted at this `ts`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_ts: Option<&'a str>,
+    /// With `thread_ts`, also show the reply in the channel.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_broadcast: Option<bool>,
+}
+
+/// Envelope shared by every Slack Web API response. Method-specific fields
+/// are flattened into `data`.
+#[derive(Debug, Deserialize)]
+pub struct SlackResponse<T> {
+    pub ok: bool,
+    pub error: Option<String>,
+    pub warning: Option<String>,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+/// Fields returned by `chat.postMessage` on success.
+#[derive(Debug, Deserialize)]
+pub struct PostedMessage {
+    pub ts: Option<String>,
+    pub channel: Option<String>,
+}
+
+/// Fields returned by `conversations.open`.
+#[derive(Debug, Deserialize)]
+struct OpenedConversation {
+    channel: ConversationId,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConversationId {
+    id: String,
+}
+
+/// A channel as listed by `conversations.list`.
+#[derive(Debug, Deserialize)]
+pub struct ChannelInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub is_member: bool,
+}
+
+/// One page of `conversations.list`.
+#[derive(Debug, Deserialize)]
+pub struct ChannelPage {
+    #[serde(default)]
+    pub channels: Vec<ChannelInfo>,
+    pub response_metadata: Option<ResponseMetadata>,
+}
+
+/// Paging info on list methods; an empty `next_cursor` means the last page.
+#[derive(Debug, Deserialize)]
+pub struct ResponseMetadata {
+    #[serde(default)]
+    pub next_cursor: String,
+}
+
+/// Fields returned by `users.lookupByEmail`.
+#[derive(Debug, Deserialize)]
+struct FoundUser {
+    user: UserId,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserId {
+    id: String,
+}
+
+/// Fields returned by `apps.connections.open`.
+#[derive(Debug, Deserialize)]
+struct SocketUrl {
+    url: String,
+}
+
+/// Fields returned by `usergroups.users.list`.
+#[derive(Debug, Deserialize)]
+struct UsergroupMembers {
+    #[serde(default)]
+    users: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum SlackError {
+    /// The token is missing, invalid, revoked or lacks a required scope.
+    Auth(String),
+    /// The channel does not exist, is archived, or the bot cannot post there.
+    Channel(String),
+    /// Slack asked us to slow down; `retry_after` comes from the header.
+    RateLimited { retry_after: Option<Duration> },
+    /// Non-2xx HTTP status without a usable Slack error payload.
+    Http { status: StatusCode, body: String },
+    /// The request never completed (DNS, TLS, connection reset, timeout...).
+    Transport(reqwest::Error),
+    /// Any other `ok: false` error code, or a response we could not parse.
+    Unknown(String),
+}
+
+impl SlackError {
+    /// Classify an `error` code from an `ok: false` response.
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "not_authed"
+            | "invalid_auth"
+            | "account_inactive"
+            | "token_revoked"
+            | "token_expired"
+            | "no_permission"
+            | "missing_scope"
+            | "not_allowed_token_type"
+            | "ekm_access_denied" => SlackError::Auth(code.to_string()),
+            "channel_not_found"
+            | "not_in_channel"
+            | "is_archived"
+            | "restricted_action"
+            | "cannot_dm_bot"
+            | "team_access_not_granted" => SlackError::Channel(code.to_string()),
+            "ratelimited" | "rate_limited" => SlackError::RateLimited { retry_after: None },
+            _ => SlackError::Unknown(code.to_string()),
+        }
+    }
+
+    /// Whether sending the same request again could succeed. Auth and channel
+    /// errors are permanent until someone fixes the config.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SlackError::Auth(_) | SlackError::Channel(_) => false,
+            SlackError::RateLimited { .. } => true,
+            // Only retry when the request provably never reached Slack; a
+            // timeout or reset after sending may already have posted.
+            SlackError::Transport(e) => e.is_connect(),
+            SlackError::Http { status, .. } => status.is_server_error(),
+            SlackError::Unknown(code) => matches!(
+                code.as_str(),
+                "internal_error" | "fatal_error" | "service_unavailable" | "request_timeout"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for SlackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlackError::Auth(code) => write!(f, "authentication error: {}", code),
+            SlackError::Channel(code) => write!(f, "channel error: {}", code),
+            SlackError::RateLimited {
+                retry_after: Some(after),
+            } => write!(f, "rate limited, retry after {}s", after.as_secs()),
+            SlackError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            SlackError::Http { status, body } => write!(f, "HTTP {}: {}", status, body),
+            SlackError::Transport(e) => write!(f, "request failed: {}", e),
+            SlackError::Unknown(code) => write!(f, "slack error: {}", code),
+        }
+    }
+}
+
+impl std::error::Error for SlackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SlackError::Transport(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Thin wrapper around the Web API holding one workspace's HTTP client, bot
+/// token and rate limiters. Every call waits for its method's limit and then
+/// for the workspace-wide `limiter`.
+pub struct SlackClient {
+    http: Client,
+    token: String,
+    limiter: RateLimiter,
+    methods: MethodLimits,
+}
+
+impl SlackClient {
+    pub fn new(http: Client, token: String, limiter: RateLimiter) -> Self {
+        SlackClient {
+            http,
+            token,
+            limiter,
+            methods: MethodLimits::default(),
+        }
+    }
+
+    pub async fn post_message(
+        &self,
+        message: &SlackMessage<'_>,
+    ) -> Result<SlackResponse<PostedMessage>, SlackError> {
+        let method = "chat.postMessage";
+        let request = self.request(method).json(message);
+        self.send(method, Some(message.channel), request).await
+    }
+
+    /// Replace the text and blocks of a message the bot posted.
+    pub async fn update_message(
+        &self,
+        channel: &str,
+        ts: &str,
+        text: &str,
+        blocks: &serde_json::Value,
+    ) -> Result<(), SlackError> {
+        let body = serde_json::json!({
+            "channel": channel,
+            "ts": ts,
+            "text": text,
+            "blocks": blocks,
+        });
+        let _: SlackResponse<serde_json::Value> = self.call("chat.update", &body).await?;
+        Ok(())
+    }
+
+    /// Open (or reuse) the DM with `user` and return its channel ID.
+    pub async fn open_dm(&self, user: &str) -> Result<String, SlackError> {
+        let body = serde_json::json!({ "users": user });
+        let response: SlackResponse<OpenedConversation> =
+            self.call("conversations.open", &body).await?;
+        Ok(response.data.channel.id)
+    }
+
+    /// User IDs of the members of a user group.
+    pub async fn usergroup_members(&self, usergroup: &str) -> Result<Vec<String>, SlackError> {
+        let response: SlackResponse<UsergroupMembers> = self
+            .call_form("usergroups.users.list", &[("usergroup", usergroup)])
+            .await?;
+        Ok(response.data.users)
+    }
+
+    /// One page of public and private channels, starting at `cursor`.
+    pub async fn list_channels(&self, cursor: Option<&str>) -> Result<ChannelPage, SlackError> {
+        let mut form = vec![
+            ("types", "public_channel,private_channel"),
+            ("exclude_archived", "true"),
+            ("limit", "200"),
+        ];
+        if let Some(cursor) = cursor {
+            form.push(("cursor", cursor));
+        }
+        let response: SlackResponse<ChannelPage> =
+            self.call_form("conversations.list", &form).await?;
+        Ok(response.data)
+    }
+
+    /// The ID of the user with this email address.
+    pub async fn lookup_user_by_email(&self, email: &str) -> Result<String, SlackError> {
+        let response: SlackResponse<FoundUser> = self
+            .call_form("users.lookupByEmail", &[("email", email)])
+            .await?;
+        Ok(response.data.user.id)
+    }
+
+    /// A fresh Socket Mode WebSocket URL. Needs a client holding the
+    /// app-level (`xapp-`) token rather than a bot token.
+    pub async fn open_connection(&self) -> Result<String, SlackError> {
+        let response: SlackResponse<SocketUrl> = self
+            .call_form("apps.connections.open", &[] as &[(&str, &str)])
+            .await?;
+        Ok(response.data.url)
+    }
+
+    /// POST a JSON body to a Web API method and parse the response envelope,
+    /// turning `ok: false` and non-2xx statuses into a [`SlackError`].
+    pub async fn call<B, T>(&self, method: &str, body: &B) -> Result<SlackResponse<T>, SlackError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let request = self.request(method).json(body);
+        self.send(method, None, request).await
+    }
+
+    /// Like [`SlackClient::call`] for read methods, which only take
+    /// form-encoded arguments.
+    pub async fn call_form<B, T>(
+        &self,
+        method: &str,
+        form: &B,
+    ) -> Result<SlackResponse<T>, SlackError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let request = self.request(method).form(form);
+        self.send(method, None, request).await
+    }
+
+    fn request(&self, method: &str) -> RequestBuilder {
+        self.http
+            .post(format!("{}/{}", API_BASE, method))
+            .bearer_auth(&self.token)
+    }
+
+    /// `channel` is the channel posted to, for per-channel limits.
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        channel: Option<&str>,
+        request: RequestBuilder,
+    ) -> Result<SlackResponse<T>, SlackError> {
+        self.methods.acquire(method, channel).await;
+        self.limiter.acquire().await;
+        let response = request.send().await.map_err(SlackError::Transport)?;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            return Err(SlackError::RateLimited { retry_after });
+        }
+
+        let text = response.text().await.map_err(SlackError::Transport)?;
+        parse_response(method, status, text)
+    }
+}
+
+/// Parse a Web API response body, turning `ok: false` and non-2xx statuses
+/// into a [`SlackError`].
+fn parse_response<T: DeserializeOwned>(
+    method: &str,
+    status: StatusCode,
+    text: String,
+) -> Result<SlackResponse<T>, SlackError> {
+    let envelope: SlackResponse<serde_json::Map<String, serde_json::Value>> =
+        match serde_json::from_str(&text) {
+            Ok(envelope) => envelope,
+            Err(_) if !status.is_success() => return Err(SlackError::Http { status, body: text }),
+            Err(e) => {
+                return Err(SlackError::Unknown(format!(
+                    "unparseable {} response: {}",
+                    method, e
+                )))
+            }
+        };
+
+    // Check the envelope before the method-specific fields, which are
+    // usually absent from `ok: false` responses.
+    if !envelope.ok {
+        return Err(match envelope.error {
+            Some(code) => SlackError::from_code(&code),
+            None if !status.is_success() => SlackError::Http { status, body: text },
+            None => SlackError::Unknown("unknown_error".to_string()),
+        });
+    }
+
+    let data = serde_json::from_value(serde_json::Value::Object(envelope.data))
+        .map_err(|e| SlackError::Unknown(format!("unexpected {} response shape: {}", method, e)))?;
+    Ok(SlackResponse {
+        ok: envelope.ok,
+        error: envelope.error,
+        warning: envelope.warning,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_error_codes() {
+        for code in ["ratelimited", "rate_limited"] {
+            let error = SlackError::from_code(code);
+            assert!(matches!(
+                error,
+                SlackError::RateLimited { retry_after: None }
+            ));
+            assert!(error.is_retryable());
+        }
+        for code in ["invalid_auth", "token_revoked", "missing_scope"] {
+            let error = SlackError::from_code(code);
+            assert!(matches!(&error, SlackError::Auth(c) if c == code));
+            assert!(!error.is_retryable());
+        }
+        for code in ["channel_not_found", "not_in_channel", "is_archived"] {
+            let error = SlackError::from_code(code);
+            assert!(matches!(&error, SlackError::Channel(c) if c == code));
+            assert!(!error.is_retryable());
+        }
+        let unknown = SlackError::from_code("msg_too_long");
+        assert!(matches!(&unknown, SlackError::Unknown(c) if c == "msg_too_long"));
+        assert!(!unknown.is_retryable());
+        assert!(SlackError::from_code("internal_error").is_retryable());
+    }
+
+    #[test]
+    fn turns_failed_responses_into_errors() {
+        let parse = |status: u16, body: &str| {
+            let status = StatusCode::from_u16(status).unwrap();
+            parse_response::<PostedMessage>("chat.postMessage", status, body.to_string())
+        };
+
+        let posted = parse(
+            200,
+            r#"{"ok":true,"channel":"C1","ts":"1.2","warning":"x"}"#,
+        )
+        .unwrap();
+        assert_eq!(posted.data.ts.as_deref(), Some("1.2"));
+        assert_eq!(posted.warning.as_deref(), Some("x"));
+
+        let error = parse(200, r#"{"ok":false,"error":"not_in_channel"}"#).unwrap_err();
+        assert!(matches!(error, SlackError::Channel(_)));
+        let error = parse(200, r#"{"ok":false,"error":"invalid_auth"}"#).unwrap_err();
+        assert!(matches!(error, SlackError::Auth(_)));
+        let error = parse(200, r#"{"ok":false}"#).unwrap_err();
+        assert!(matches!(&error, SlackError::Unknown(c) if c == "unknown_error"));
+
+        // Gateways answer with HTML; only server errors are worth a retry
+        let error = parse(502, "<html>Bad Gateway</html>").unwrap_err();
+        assert!(matches!(error, SlackError::Http { .. }) && error.is_retryable());
+        let error = parse(404, "not found").unwrap_err();
+        assert!(matches!(error, SlackError::Http { .. }) && !error.is_retryable());
+        let error = parse(200, "not json").unwrap_err();
+        assert!(matches!(error, SlackError::Unknown(_)) && !error.is_retryable());
+    }
+}
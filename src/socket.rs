@@ -0,0 +1,428 @@
+use crate::commands::{self, CommandRequest, SlashCommand};
+use crate::events::{self, Event};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::future::Future;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tokio_util::sync::CancellationToken;
+
+/// Delay before reconnecting after a failure, doubled up to `MAX_BACKOFF`.
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// A Socket Mode message. Everything but `hello` and `disconnect` carries an
+/// `envelope_id` that must be acknowledged within three seconds.
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    #[serde(rename = "type")]
+    kind: String,
+    envelope_id: Option<String>,
+    #[serde(default)]
+    payload: serde_json::Value,
+    reason: Option<String>,
+}
+
+/// How a connection ended.
+enum Ended {
+    Shutdown,
+    /// Slack asked us to reconnect (`disconnect`); not an error.
+    Disconnected(String),
+    Failed(String),
+}
+
+/// Keep a Socket Mode connection open until `shutdown`, feeding slash
+/// commands, button presses and reactions to the same handlers as the HTTP
+/// endpoints. `open` returns a
+/// fresh WebSocket URL (`apps.connections.open`) for every connection, as
+/// Slack's URLs are single-use.
+pub async fn run<F, Fut>(
+    mut open: F,
+    requests: mpsc::Sender<CommandRequest>,
+    events: mpsc::Sender<Event>,
+    shutdown: CancellationToken,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let mut backoff = MIN_BACKOFF;
+    while !shutdown.is_cancelled() {
+        let mut greeted = false;
+        let connected = match open().await {
+            Ok(url) => tokio_tungstenite::connect_async(url.as_str())
+                .await
+                .map(|(socket, _)| socket)
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        let ended = match connected {
+            Ok(socket) => session(socket, &requests, &events, &shutdown, &mut greeted).await,
+            Err(e) => Ended::Failed(format!("could not connect: {}", e)),
+        };
+        if greeted {
+            backoff = MIN_BACKOFF;
+        }
+        match ended {
+            Ended::Shutdown => return,
+            Ended::Disconnected(reason) => {
+                println!("Socket Mode disconnect ({}), reconnecting", reason);
+                continue;
+            }
+            Ended::Failed(e) => {
+                eprintln!(
+                    "Socket Mode connection lost: {}; reconnecting in {}s",
+                    e,
+                    backoff.as_secs()
+                );
+            }
+        }
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = tokio::time::sleep(backoff) => {}
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Serve one connection. Acks are written by this task; slash commands are
+/// answered from spawned tasks so a slow reply does not hold up other
+/// envelopes.
+async fn session(
+    socket: Socket,
+    requests: &mpsc::Sender<CommandRequest>,
+    events: &mpsc::Sender<Event>,
+    shutdown: &CancellationToken,
+    greeted: &mut bool,
+) -> Ended {
+    let (mut sink, mut stream) = socket.split();
+    let (ack_tx, mut ack_rx) = mpsc::unbounded_channel::<serde_json::Value>();
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => {
+                let _ = sink.send(Message::Close(None)).await;
+                return Ended::Shutdown;
+            }
+            Some(ack) = ack_rx.recv() => {
+                if let Err(e) = sink.send(Message::Text(ack.to_string())).await {
+                    return Ended::Failed(e.to_string());
+                }
+            }
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => {
+                        return Ended::Failed("closed by Slack".to_string())
+                    }
+                    // Pings are answered by tungstenite itself
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Ended::Failed(e.to_string()),
+                };
+                let envelope: Envelope = match serde_json::from_str(&text) {
+                    Ok(envelope) => envelope,
+                    Err(e) => {
+                        eprintln!("Ignoring malformed Socket Mode message: {}", e);
+                        continue;
+                    }
+                };
+                match envelope.kind.as_str() {
+                    "hello" => {
+                        println!("Socket Mode connected");
+                        *greeted = true;
+                    }
+                    "disconnect" => {
+                        return Ended::Disconnected(
+                            envelope.reason.unwrap_or_else(|| "no reason given".to_string()),
+                        )
+                    }
+                    _ => handle(envelope, requests, events, &ack_tx),
+                }
+            }
+        }
+    }
+}
+
+/// Acknowledge an envelope, answering slash commands in the ack itself.
+fn handle(
+    envelope: Envelope,
+    requests: &mpsc::Sender<CommandRequest>,
+    events: &mpsc::Sender<Event>,
+    acks: &mpsc::UnboundedSender<serde_json::Value>,
+) {
+    let Some(envelope_id) = envelope.envelope_id else {
+        eprintln!(
+            "Ignoring Socket Mode '{}' without envelope_id",
+            envelope.kind
+        );
+        return;
+    };
+    if envelope.kind != "slash_commands" {
+        // Button presses and reactions are handled after the ack; anything
+        // else is only acknowledged, which stops Slack from retrying.
+        let event = match envelope.kind.as_str() {
+            "interactive" => events::from_interaction(&envelope.payload),
+            "events_api" => events::from_callback(&envelope.payload),
+            _ => None,
+        };
+        match event {
+            Some(event) => {
+                if let Err(e) = events.try_send(event) {
+                    eprintln!("Dropping Slack event: {}", e);
+                }
+            }
+            None => println!("Acknowledged Socket Mode '{}' envelope", envelope.kind),
+        }
+        let _ = acks.send(serde_json::json!({ "envelope_id": envelope_id }));
+        return;
+    }
+    let command: SlashCommand = match serde_json::from_value(envelope.payload) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("Malformed slash command over Socket Mode: {}", e);
+            let _ = acks.send(serde_json::json!({ "envelope_id": envelope_id }));
+            return;
+        }
+    };
+    let requests = requests.clone();
+    let acks = acks.clone();
+    tokio::spawn(async move {
+        // dispatch() answers within REPLY_TIMEOUT, inside Slack's deadline
+        let text = commands::dispatch(command, &requests).await;
+        let _ = acks.send(serde_json::json!({
+            "envelope_id": envelope_id,
+            "payload": { "response_type": "ephemeral", "text": text },
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buttons;
+    use crate::commands::Command;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    const DEADLINE: Duration = Duration::from_secs(5);
+
+    type Server = WebSocketStream<TcpStream>;
+
+    async fn accept(listener: &TcpListener) -> Server {
+        let (tcp, _) = tokio::time::timeout(DEADLINE, listener.accept())
+            .await
+            .expect("client connects")
+            .unwrap();
+        tokio_tungstenite::accept_async(tcp).await.unwrap()
+    }
+
+    async fn send(server: &mut Server, message: serde_json::Value) {
+        server
+            .send(Message::Text(message.to_string()))
+            .await
+            .unwrap();
+    }
+
+    async fn receive(server: &mut Server) -> serde_json::Value {
+        loop {
+            let message = tokio::time::timeout(DEADLINE, server.next())
+                .await
+                .expect("client acknowledges in time")
+                .unwrap()
+                .unwrap();
+            if let Message::Text(text) = message {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    /// Runs against a local stand-in for Slack's Socket Mode endpoint.
+    #[tokio::test]
+    async fn acks_envelopes_and_reconnects_on_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let opened = Arc::new(AtomicUsize::new(0));
+        let (requests_tx, mut requests_rx) = mpsc::channel(1);
+        let (events_tx, mut events_rx) = mpsc::channel(1);
+        let shutdown = CancellationToken::new();
+        let client = tokio::spawn(run(
+            {
+                let opened = Arc::clone(&opened);
+                move || {
+                    opened.fetch_add(1, Ordering::SeqCst);
+                    let url = url.clone();
+                    async move { Ok(url) }
+                }
+            },
+            requests_tx,
+            events_tx,
+            shutdown.clone(),
+        ));
+
+        let mut server = accept(&listener).await;
+        send(&mut server, serde_json::json!({ "type": "hello" })).await;
+
+        // Events are acknowledged right away
+        send(
+            &mut server,
+            serde_json::json!({
+                "type": "events_api",
+                "envelope_id": "event-1",
+                "payload": { "event": { "type": "app_mention" } },
+            }),
+        )
+        .await;
+        assert_eq!(
+            receive(&mut server).await,
+            serde_json::json!({ "envelope_id": "event-1" })
+        );
+
+        // So are button presses, which are then handed on
+        let target = buttons::Target {
+            reminder: "standup".to_string(),
+            workspace: "default".to_string(),
+            timezone: "UTC".to_string(),
+        };
+        let blocks = buttons::attach(None, "Standup!", &target);
+        let done = &blocks[1]["elements"][0];
+        send(
+            &mut server,
+            serde_json::json!({
+                "type": "interactive",
+                "envelope_id": "press-1",
+                "payload": {
+                    "type": "block_actions",
+                    "user": { "id": "U123" },
+                    "channel": { "id": "C123" },
+                    "message": { "ts": "1700000000.000100", "text": "Standup!", "blocks": blocks },
+                    "actions": [{ "action_id": done["action_id"], "value": done["value"] }],
+                },
+            }),
+        )
+        .await;
+        assert_eq!(
+            receive(&mut server).await,
+            serde_json::json!({ "envelope_id": "press-1" })
+        );
+        let event = tokio::time::timeout(DEADLINE, events_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let Event::Press(press) = event else {
+            panic!("expected a button press, got {:?}", event);
+        };
+        assert_eq!(press.action, buttons::Action::Done);
+        assert_eq!(press.target.reminder, "standup");
+        assert_eq!(press.ts, "1700000000.000100");
+        assert_eq!(press.blocks.as_array().map(Vec::len), Some(1));
+
+        // Reactions count as acknowledgements
+        send(
+            &mut server,
+            serde_json::json!({
+                "type": "events_api",
+                "envelope_id": "event-2",
+                "payload": {
+                    "type": "event_callback",
+                    "event": {
+                        "type": "reaction_added",
+                        "user": "U456",
+                        "reaction": "white_check_mark",
+                        "item": { "type": "message", "channel": "C123", "ts": "1700000000.000100" },
+                    },
+                },
+            }),
+        )
+        .await;
+        assert_eq!(
+            receive(&mut server).await,
+            serde_json::json!({ "envelope_id": "event-2" })
+        );
+        let event = tokio::time::timeout(DEADLINE, events_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(&event, Event::Reaction { user, reaction, .. } if user == "U456" && reaction == "white_check_mark"),
+            "expected a reaction, got {:?}",
+            event
+        );
+
+        // Slash commands go to the command handlers; the reply rides on the ack
+        send(
+            &mut server,
+            serde_json::json!({
+                "type": "slash_commands",
+                "envelope_id": "command-1",
+                "payload": {
+                    "command": "/remind-bot",
+                    "text": "list",
+                    "user_id": "U123",
+                    "channel_id": "C123",
+                },
+            }),
+        )
+        .await;
+        let request = tokio::time::timeout(DEADLINE, requests_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(request.invocation.command, Command::List);
+        assert_eq!(request.invocation.user_id, "U123");
+        request.reply.send("No reminders.".to_string()).unwrap();
+        assert_eq!(
+            receive(&mut server).await,
+            serde_json::json!({
+                "envelope_id": "command-1",
+                "payload": { "response_type": "ephemeral", "text": "No reminders." },
+            })
+        );
+
+        // A disconnect makes the client fetch a new URL and come back
+        send(
+            &mut server,
+            serde_json::json!({ "type": "disconnect", "reason": "refresh_requested" }),
+        )
+        .await;
+        let mut server = accept(&listener).await;
+        send(&mut server, serde_json::json!({ "type": "hello" })).await;
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+
+        shutdown.cancel();
+        tokio::time::timeout(DEADLINE, client)
+            .await
+            .expect("client stops on shutdown")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn retries_when_the_url_cannot_be_opened() {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let (requests_tx, _requests_rx) = mpsc::channel(1);
+        let shutdown = CancellationToken::new();
+        let client = tokio::spawn(run(
+            {
+                let opened = Arc::clone(&opened);
+                let shutdown = shutdown.clone();
+                move || {
+                    if opened.fetch_add(1, Ordering::SeqCst) == 1 {
+                        shutdown.cancel();
+                    }
+                    async { Err("invalid_auth".to_string()) }
+                }
+            },
+            requests_tx,
+            mpsc::channel(1).0,
+            shutdown,
+        ));
+        tokio::time::timeout(DEADLINE, client)
+            .await
+            .expect("client retries after the backoff")
+            .unwrap();
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,770 @@
+use crate::config::Escalation;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::task;
+
+/// Default location of the state file when the config does not set one.
+pub const DEFAULT_STATE_PATH: &str = "reminder-state.json";
+
+/// Ledger entries (one per occurrence and destination) kept per reminder;
+/// older ones are pruned on write.
+const LEDGER_ENTRIES_PER_REMINDER: usize = 500;
+
+/// Counted occurrences remembered per reminder to keep `fire_count` exact.
+const COUNTED_PER_REMINDER: usize = 500;
+
+/// Everything persisted between runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    #[serde(default)]
+    reminders: HashMap<String, ReminderState>,
+    /// Delivery ledger, per reminder, ordered by scheduled time.
+    #[serde(default)]
+    deliveries: BTreeMap<String, Vec<Delivery>>,
+    /// Reminders created with `/remind-bot add`, by name.
+    #[serde(default)]
+    created: BTreeMap<String, StoredReminder>,
+    /// Number of reminders ever created with `/remind-bot add`, for naming.
+    #[serde(default)]
+    created_count: u64,
+    /// Reminders paused with `/remind-bot pause`.
+    #[serde(default)]
+    paused: BTreeSet<String>,
+    /// Snoozed posts waiting to be re-posted, by ID.
+    #[serde(default)]
+    snoozes: BTreeMap<u64, Snooze>,
+    /// Number of snoozes ever recorded, for IDs.
+    #[serde(default)]
+    snooze_count: u64,
+    /// Posts still waiting for acknowledgement, by ID.
+    #[serde(default)]
+    pending_acks: BTreeMap<u64, PendingAck>,
+    /// Number of acknowledgements ever awaited, for IDs.
+    #[serde(default)]
+    pending_ack_count: u64,
+    /// Bumped on every change, to order snapshots for writing.
+    #[serde(skip)]
+    version: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderState {
+    /// Scheduled time of the last occurrence that was posted successfully.
+    pub last_fired: DateTime<Utc>,
+    /// Wall-clock time the post actually went out.
+    pub sent_at: DateTime<Utc>,
+    /// Number of occurrences posted so far.
+    #[serde(default)]
+    pub fire_count: u64,
+    /// Scheduled times of the latest counted occurrences, so an occurrence
+    /// posted to several destinations is counted once.
+    #[serde(default)]
+    pub counted: BTreeSet<DateTime<Utc>>,
+    /// Latest post per destination key, for threading follow-ups.
+    #[serde(default)]
+    pub last_posts: BTreeMap<String, PostRef>,
+}
+
+/// A message the bot posted, as returned by `chat.postMessage`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostRef {
+    pub channel: String,
+    pub ts: String,
+    /// Set when the message was itself a thread reply.
+    pub thread_ts: Option<String>,
+    pub posted_at: DateTime<Utc>,
+}
+
+impl PostRef {
+    /// The `ts` replies must use: Slack threads are one level deep, so a
+    /// reply to a reply goes under the original parent.
+    pub fn thread_root(&self) -> &str {
+        self.thread_ts.as_deref().unwrap_or(&self.ts)
+    }
+}
+
+/// A reminder created from Slack rather than the config file. Destinations
+/// are stored already resolved to IDs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredReminder {
+    pub name: String,
+    pub workspace: String,
+    /// Text form of the schedule, see `Schedule`'s `Display`.
+    #[serde(alias = "cron")]
+    pub schedule: String,
+    pub timezone: String,
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub usergroups: Vec<String>,
+    pub text: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A post someone snoozed, to be posted again at `due`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snooze {
+    #[serde(default)]
+    pub id: u64,
+    pub reminder: String,
+    pub workspace: String,
+    pub timezone: String,
+    pub channel: String,
+    pub thread_ts: Option<String>,
+    pub text: String,
+    /// The post's blocks without the buttons, which are added again.
+    pub blocks: Option<serde_json::Value>,
+    pub due: DateTime<Utc>,
+    pub snoozed_by: String,
+}
+
+/// An occurrence of a reminder that nobody has acknowledged yet, with what
+/// is needed to escalate it even if the reminder changes meanwhile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingAck {
+    #[serde(default)]
+    pub id: u64,
+    pub reminder: String,
+    pub workspace: String,
+    pub text: String,
+    /// Blocks as posted, buttons included.
+    pub blocks: Option<serde_json::Value>,
+    /// Every post of the occurrence, re-posts included; acknowledging any
+    /// one of them counts.
+    pub posts: Vec<PostRef>,
+    /// Reaction names that count; any reaction does when empty.
+    #[serde(default)]
+    pub reactions: Vec<String>,
+    pub escalate: Vec<Escalation>,
+    pub within_minutes: i64,
+    pub posted_at: DateTime<Utc>,
+    /// Index of the next escalation step.
+    #[serde(default)]
+    pub next_step: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Written before `chat.postMessage`; if it is still pending after a
+    /// restart the process died mid-send and the post may or may not exist.
+    Pending,
+    Sent,
+    Failed,
+}
+
+/// One occurrence of a reminder to one destination, identified by
+/// (reminder, scheduled, destination).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub scheduled: DateTime<Utc>,
+    /// Destination key, see `Destination::key`.
+    #[serde(default)]
+    pub destination: String,
+    pub status: DeliveryStatus,
+    pub attempted_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub channel: Option<String>,
+    pub ts: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum StateError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, serde_json::Error),
+    Serialize(PathBuf, serde_json::Error),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Io(path, e) => write!(f, "state file {}: {}", path.display(), e),
+            StateError::Parse(path, e) => {
+                write!(f, "state file {} is corrupt: {}", path.display(), e)
+            }
+            StateError::Serialize(path, e) => {
+                write!(f, "could not serialize state for {}: {}", path.display(), e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// JSON file recording the last successful fire of each reminder. Every
+/// update is written through to disk so a crash loses at most the send in
+/// flight.
+pub struct StateStore {
+    path: PathBuf,
+    state: Mutex<State>,
+    /// Version of the last snapshot written to disk.
+    written: tokio::sync::Mutex<u64>,
+}
+
+impl StateStore {
+    /// Open the store at `path`; a missing file starts an empty store.
+    pub fn open(path: &Path) -> Result<Self, StateError> {
+        let state = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| StateError::Parse(path.to_path_buf(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => State::default(),
+            Err(e) => return Err(StateError::Io(path.to_path_buf(), e)),
+        };
+        Ok(StateStore {
+            path: path.to_path_buf(),
+            state: Mutex::new(state),
+            written: tokio::sync::Mutex::new(0),
+        })
+    }
+
+    pub fn fire_count(&self, reminder: &str) -> u64 {
+        let state = self.state.lock().unwrap();
+        state
+            .reminders
+            .get(reminder)
+            .map_or(0, |entry| entry.fire_count)
+    }
+
+    /// Latest post of `reminder` to the destination with key `destination`.
+    pub fn last_post(&self, reminder: &str, destination: &str) -> Option<PostRef> {
+        let state = self.state.lock().unwrap();
+        state
+            .reminders
+            .get(reminder)
+            .and_then(|entry| entry.last_posts.get(destination).cloned())
+    }
+
+    pub fn last_fired(&self, reminder: &str) -> Option<DateTime<Utc>> {
+        let state = self.state.lock().unwrap();
+        state.reminders.get(reminder).map(|entry| entry.last_fired)
+    }
+
+    /// Record that the occurrence scheduled at `occurrence` was posted to at
+    /// least one destination. Recording the same occurrence again, in any
+    /// order, is a no-op.
+    pub async fn record_fired(
+        &self,
+        reminder: &str,
+        occurrence: DateTime<Utc>,
+    ) -> Result<(), StateError> {
+        self.update(|state| {
+            match state.reminders.get_mut(reminder) {
+                Some(entry) => {
+                    // `last_fired` also covers state files from before `counted`
+                    if entry.last_fired == occurrence || !entry.counted.insert(occurrence) {
+                        return ((), false);
+                    }
+                    entry.fire_count += 1;
+                    while entry.counted.len() > COUNTED_PER_REMINDER {
+                        entry.counted.pop_first();
+                    }
+                    // Never move backwards, e.g. when a late catch-up finishes
+                    // after a newer occurrence.
+                    if occurrence > entry.last_fired {
+                        entry.last_fired = occurrence;
+                        entry.sent_at = Utc::now();
+                    }
+                }
+                None => {
+                    state.reminders.insert(
+                        reminder.to_string(),
+                        ReminderState {
+                            last_fired: occurrence,
+                            sent_at: Utc::now(),
+                            fire_count: 1,
+                            counted: BTreeSet::from([occurrence]),
+                            last_posts: BTreeMap::new(),
+                        },
+                    );
+                }
+            }
+            ((), true)
+        })
+        .await
+    }
+
+    /// Remember where a reminder was last posted for one destination.
+    pub async fn record_post(
+        &self,
+        reminder: &str,
+        destination: &str,
+        post: PostRef,
+    ) -> Result<(), StateError> {
+        self.update(|state| {
+            let Some(entry) = state.reminders.get_mut(reminder) else {
+                return ((), false);
+            };
+            entry.last_posts.insert(destination.to_string(), post);
+            ((), true)
+        })
+        .await
+    }
+
+    /// Claim an occurrence in the ledger before posting it. Returns the
+    /// existing entry instead if the occurrence was already sent or is still
+    /// pending from an earlier attempt, in which case the caller must not
+    /// post. Occurrences that failed outright may be claimed again.
+    pub async fn begin_delivery(
+        &self,
+        reminder: &str,
+        scheduled: DateTime<Utc>,
+        destination: &str,
+    ) -> Result<Option<Delivery>, StateError> {
+        self.update(|state| {
+            let entries = state.deliveries.entry(reminder.to_string()).or_default();
+            let pending = Delivery {
+                scheduled,
+                destination: destination.to_string(),
+                status: DeliveryStatus::Pending,
+                attempted_at: Utc::now(),
+                completed_at: None,
+                channel: None,
+                ts: None,
+                error: None,
+            };
+            match entries
+                .iter_mut()
+                .find(|entry| entry.scheduled == scheduled && entry.destination == destination)
+            {
+                Some(existing) if existing.status != DeliveryStatus::Failed => {
+                    return (Some(existing.clone()), false)
+                }
+                Some(existing) => *existing = pending,
+                None => {
+                    entries.push(pending);
+                    entries.sort_by(|a, b| {
+                        (a.scheduled, &a.destination).cmp(&(b.scheduled, &b.destination))
+                    });
+                    if entries.len() > LEDGER_ENTRIES_PER_REMINDER {
+                        let excess = entries.len() - LEDGER_ENTRIES_PER_REMINDER;
+                        entries.drain(..excess);
+                    }
+                }
+            }
+            (None, true)
+        })
+        .await
+    }
+
+    /// Settle a claimed occurrence with the outcome of the post: the
+    /// channel and `ts` Slack returned, or the error.
+    pub async fn finish_delivery(
+        &self,
+        reminder: &str,
+        scheduled: DateTime<Utc>,
+        destination: &str,
+        outcome: Result<(Option<String>, Option<String>), String>,
+    ) -> Result<(), StateError> {
+        self.update(|state| {
+            let Some(entry) = state.deliveries.get_mut(reminder).and_then(|entries| {
+                entries
+                    .iter_mut()
+                    .find(|entry| entry.scheduled == scheduled && entry.destination == destination)
+            }) else {
+                return ((), false);
+            };
+            entry.completed_at = Some(Utc::now());
+            match outcome {
+                Ok((channel, ts)) => {
+                    entry.status = DeliveryStatus::Sent;
+                    entry.channel = channel;
+                    entry.ts = ts;
+                }
+                Err(error) => {
+                    entry.status = DeliveryStatus::Failed;
+                    entry.error = Some(error);
+                }
+            }
+            ((), true)
+        })
+        .await
+    }
+
+    /// Ledger entries, optionally limited to one reminder, for debugging.
+    pub fn deliveries(&self, reminder: Option<&str>) -> Vec<(String, Delivery)> {
+        let state = self.state.lock().unwrap();
+        state
+            .deliveries
+            .iter()
+            .filter(|(name, _)| reminder.is_none_or(|wanted| wanted == name.as_str()))
+            .flat_map(|(name, entries)| {
+                entries
+                    .iter()
+                    .map(move |entry| (name.clone(), entry.clone()))
+            })
+            .collect()
+    }
+
+    pub fn stored_reminders(&self) -> Vec<StoredReminder> {
+        let state = self.state.lock().unwrap();
+        state.created.values().cloned().collect()
+    }
+
+    /// Persist a reminder created from Slack under the next free
+    /// `slack-<n>` name, which is returned.
+    pub async fn add_stored(&self, mut reminder: StoredReminder) -> Result<String, StateError> {
+        self.update(|state| {
+            let name = loop {
+                state.created_count += 1;
+                let name = format!("slack-{}", state.created_count);
+                if !state.created.contains_key(&name) {
+                    break name;
+                }
+            };
+            reminder.name = name.clone();
+            state.created.insert(name.clone(), reminder);
+            (name, true)
+        })
+        .await
+    }
+
+    /// Remove a reminder created from Slack. Returns `false` if there was
+    /// none by that name.
+    pub async fn remove_stored(&self, name: &str) -> Result<bool, StateError> {
+        self.update(|state| {
+            if state.created.remove(name).is_none() {
+                return (false, false);
+            }
+            state.paused.remove(name);
+            (true, true)
+        })
+        .await
+    }
+
+    pub fn paused(&self) -> BTreeSet<String> {
+        let state = self.state.lock().unwrap();
+        state.paused.clone()
+    }
+
+    /// Pause or resume a reminder. Returns `false` if it already was.
+    pub async fn set_paused(&self, name: &str, paused: bool) -> Result<bool, StateError> {
+        self.update(|state| {
+            let changed = if paused {
+                state.paused.insert(name.to_string())
+            } else {
+                state.paused.remove(name)
+            };
+            (changed, changed)
+        })
+        .await
+    }
+
+    pub fn snoozes(&self) -> Vec<Snooze> {
+        let state = self.state.lock().unwrap();
+        state.snoozes.values().cloned().collect()
+    }
+
+    /// Persist a snooze under a new ID, which is returned.
+    pub async fn add_snooze(&self, mut snooze: Snooze) -> Result<u64, StateError> {
+        self.update(|state| {
+            state.snooze_count += 1;
+            snooze.id = state.snooze_count;
+            state.snoozes.insert(snooze.id, snooze);
+            (state.snooze_count, true)
+        })
+        .await
+    }
+
+    /// Forget a snooze once it has been re-posted.
+    pub async fn remove_snooze(&self, id: u64) -> Result<(), StateError> {
+        self.update(|state| ((), state.snoozes.remove(&id).is_some()))
+            .await
+    }
+
+    pub fn pending_acks(&self) -> Vec<PendingAck> {
+        let state = self.state.lock().unwrap();
+        state.pending_acks.values().cloned().collect()
+    }
+
+    pub fn pending_ack(&self, id: u64) -> Option<PendingAck> {
+        let state = self.state.lock().unwrap();
+        state.pending_acks.get(&id).cloned()
+    }
+
+    /// Start waiting for acknowledgement of an occurrence under a new ID,
+    /// which is returned along with the reminder's earlier pending entries,
+    /// now dropped: a new occurrence supersedes them.
+    pub async fn add_pending_ack(
+        &self,
+        mut pending: PendingAck,
+    ) -> Result<(u64, Vec<PendingAck>), StateError> {
+        self.update(|state| {
+            let superseded: Vec<u64> = state
+                .pending_acks
+                .values()
+                .filter(|entry| entry.reminder == pending.reminder)
+                .map(|entry| entry.id)
+                .collect();
+            let superseded = superseded
+                .into_iter()
+                .filter_map(|id| state.pending_acks.remove(&id))
+                .collect();
+            state.pending_ack_count += 1;
+            pending.id = state.pending_ack_count;
+            state.pending_acks.insert(pending.id, pending);
+            ((state.pending_ack_count, superseded), true)
+        })
+        .await
+    }
+
+    /// Record a re-post and that escalation has reached `next_step`.
+    /// Returns `false` if the entry was acknowledged meanwhile.
+    pub async fn advance_pending_ack(
+        &self,
+        id: u64,
+        next_step: usize,
+        reposts: Vec<PostRef>,
+    ) -> Result<bool, StateError> {
+        self.update(|state| {
+            let Some(entry) = state.pending_acks.get_mut(&id) else {
+                return (false, false);
+            };
+            entry.next_step = next_step;
+            entry.posts.extend(reposts);
+            (true, true)
+        })
+        .await
+    }
+
+    /// Stop waiting for the occurrence posted as (`channel`, `ts`), if one
+    /// is pending and `accepts` agrees, and return it.
+    pub async fn acknowledge(
+        &self,
+        channel: &str,
+        ts: &str,
+        accepts: impl Fn(&PendingAck) -> bool,
+    ) -> Result<Option<PendingAck>, StateError> {
+        self.update(|state| {
+            let Some(id) = state
+                .pending_acks
+                .values()
+                .find(|entry| {
+                    entry
+                        .posts
+                        .iter()
+                        .any(|post| post.channel == channel && post.ts == ts)
+                })
+                .filter(|entry| accepts(entry))
+                .map(|entry| entry.id)
+            else {
+                return (None, false);
+            };
+            (state.pending_acks.remove(&id), true)
+        })
+        .await
+    }
+
+    /// Write the current state to disk, e.g. before shutting down.
+    pub async fn flush(&self) -> Result<(), StateError> {
+        self.update(|_| ((), true)).await
+    }
+
+    /// Apply `change` under the lock and, if it reports a change, write the
+    /// new state to disk once the lock is released.
+    async fn update<T>(
+        &self,
+        change: impl FnOnce(&mut State) -> (T, bool),
+    ) -> Result<T, StateError> {
+        let (value, version, json) = {
+            let mut state = self.state.lock().unwrap();
+            let (value, changed) = change(&mut state);
+            if !changed {
+                return Ok(value);
+            }
+            state.version += 1;
+            let json = serde_json::to_string_pretty(&*state)
+                .map_err(|e| StateError::Serialize(self.path.clone(), e))?;
+            (value, state.version, json)
+        };
+        self.write(version, json).await?;
+        Ok(value)
+    }
+
+    /// Write the snapshot `json` of `version` on a blocking thread, one
+    /// write at a time. A snapshot older than one already written is
+    /// skipped, since the newer one includes its changes.
+    async fn write(&self, version: u64, json: String) -> Result<(), StateError> {
+        let mut written = self.written.lock().await;
+        if *written >= version {
+            return Ok(());
+        }
+        let path = self.path.clone();
+        task::spawn_blocking(move || write_file(&path, &json))
+            .await
+            .map_err(|e| StateError::Io(self.path.clone(), std::io::Error::other(e)))??;
+        *written = version;
+        Ok(())
+    }
+}
+
+/// Write to a temporary file and rename it over the old one so a crash
+/// mid-write never leaves a truncated state file behind. Both the file and
+/// the rename are synced, so a write that returned survives a power loss.
+fn write_file(path: &Path, json: &str) -> Result<(), StateError> {
+    let tmp = path.with_extension("json.tmp");
+    let io = |path: &Path| {
+        let path = path.to_path_buf();
+        move |e| StateError::Io(path, e)
+    };
+    let mut file = File::create(&tmp).map_err(io(&tmp))?;
+    file.write_all(json.as_bytes()).map_err(io(&tmp))?;
+    file.sync_all().map_err(io(&tmp))?;
+    drop(file);
+    fs::rename(&tmp, path).map_err(io(path))?;
+    sync_dir(path).map_err(io(path))
+}
+
+/// Sync the directory holding `path` so a rename into it is durable.
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+/// Directories cannot be opened for syncing here; the rename is left to the
+/// file system.
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    async fn claim(
+        store: &StateStore,
+        at: DateTime<Utc>,
+        destination: &str,
+    ) -> Option<DeliveryStatus> {
+        store
+            .begin_delivery("standup", at, destination)
+            .await
+            .unwrap()
+            .map(|existing| existing.status)
+    }
+
+    #[tokio::test]
+    async fn ledger_claims_each_occurrence_once() {
+        let path = std::env::temp_dir().join(format!("ledger-test-{}.json", std::process::id()));
+        let store = StateStore::open(&path).unwrap();
+        let nine = Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap();
+
+        // The first claim wins; repeats see it pending, other destinations
+        // and occurrences are claimed separately
+        assert_eq!(claim(&store, nine, "C1").await, None);
+        assert_eq!(
+            claim(&store, nine, "C1").await,
+            Some(DeliveryStatus::Pending)
+        );
+        assert_eq!(claim(&store, nine, "C2").await, None);
+        assert_eq!(
+            claim(&store, nine + chrono::Duration::days(1), "C1").await,
+            None
+        );
+
+        let sent = Ok((
+            Some("C1".to_string()),
+            Some("1700000000.000100".to_string()),
+        ));
+        store
+            .finish_delivery("standup", nine, "C1", sent)
+            .await
+            .unwrap();
+        let existing = store
+            .begin_delivery("standup", nine, "C1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(existing.status, DeliveryStatus::Sent);
+        assert_eq!(existing.ts.as_deref(), Some("1700000000.000100"));
+
+        // A failed post may be claimed again
+        let failed = Err("channel_not_found".to_string());
+        store
+            .finish_delivery("standup", nine, "C2", failed)
+            .await
+            .unwrap();
+        assert_eq!(claim(&store, nine, "C2").await, None);
+        assert_eq!(
+            claim(&store, nine, "C2").await,
+            Some(DeliveryStatus::Pending)
+        );
+
+        // Claims survive a restart, so a crash mid-send is not re-posted
+        let reopened = StateStore::open(&path).unwrap();
+        assert_eq!(
+            claim(&reopened, nine, "C1").await,
+            Some(DeliveryStatus::Sent)
+        );
+        assert_eq!(
+            claim(&reopened, nine, "C2").await,
+            Some(DeliveryStatus::Pending)
+        );
+        assert_eq!(reopened.deliveries(Some("standup")).len(), 3);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn occurrences_are_counted_once_in_any_order() {
+        let path = std::env::temp_dir().join(format!("fired-test-{}.json", std::process::id()));
+        let store = StateStore::open(&path).unwrap();
+        let nine = Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap();
+        let ten = nine + chrono::Duration::hours(1);
+
+        // A late catch-up of 09:00 settles after 10:00 was posted, each to
+        // two destinations
+        for occurrence in [ten, nine, nine, ten, ten] {
+            store.record_fired("standup", occurrence).await.unwrap();
+        }
+        assert_eq!(store.fire_count("standup"), 2);
+        assert_eq!(store.last_fired("standup"), Some(ten));
+
+        let reopened = StateStore::open(&path).unwrap();
+        reopened.record_fired("standup", nine).await.unwrap();
+        assert_eq!(reopened.fire_count("standup"), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn concurrent_updates_leave_the_latest_state_on_disk() {
+        let path = std::env::temp_dir().join(format!("writes-test-{}.json", std::process::id()));
+        let store = std::sync::Arc::new(StateStore::open(&path).unwrap());
+        let nine = Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap();
+        let claims: Vec<_> = (0..20)
+            .map(|i| {
+                let store = std::sync::Arc::clone(&store);
+                tokio::spawn(async move {
+                    let destination = format!("C{}", i);
+                    store
+                        .begin_delivery("standup", nine, &destination)
+                        .await
+                        .unwrap();
+                })
+            })
+            .collect();
+        for claim in claims {
+            claim.await.unwrap();
+        }
+        let reopened = StateStore::open(&path).unwrap();
+        assert_eq!(reopened.deliveries(Some("standup")).len(), 20);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
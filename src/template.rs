@@ -0,0 +1,232 @@
+use crate::schedule::Occurrence;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Variables filled in by the bot for every occurrence.
+pub const BUILTIN_VARS: &[&str] = &[
+    "reminder_name",
+    "fire_time_local",
+    "fire_time_utc",
+    "fire_time_iso",
+    "fire_date",
+    "fire_time",
+    "fire_weekday",
+    "week_number",
+    "occurrence_count",
+    "next_fire",
+    "next_fire_date",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Var(String),
+}
+
+/// A string with `{{ variable }}` placeholders. `{{{{` stands for a literal
+/// `{{`, so `{{{{name}}` renders as `{{name}}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            let after = &rest[start + 2..];
+            if let Some(escaped) = after.strip_prefix("{{") {
+                parts.push(Part::Literal("{{".to_string()));
+                rest = escaped;
+                continue;
+            }
+            let end = after
+                .find("}}")
+                .ok_or_else(|| format!("unclosed '{{{{' in \"{}\"", source))?;
+            let name = after[..end].trim();
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("invalid variable name '{{{{{}}}}}'", &after[..end]));
+            }
+            parts.push(Part::Var(name.to_string()));
+            rest = &after[end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+        Ok(Template { parts })
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Var(name) => Some(name.as_str()),
+            Part::Literal(_) => None,
+        })
+    }
+
+    /// Substitute every placeholder. Unknown names render as-is so a bad
+    /// variable never drops a reminder; they are rejected at load time.
+    pub fn render(&self, vars: &HashMap<String, String>) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => out.push_str(text),
+                Part::Var(name) => match vars.get(name) {
+                    Some(value) => out.push_str(value),
+                    None => {
+                        out.push_str("{{");
+                        out.push_str(name);
+                        out.push_str("}}");
+                    }
+                },
+            }
+        }
+        out
+    }
+}
+
+/// Parse `source` and check that every placeholder is a built-in or one of
+/// `user_vars`.
+pub fn validate(source: &str, user_vars: &BTreeMap<String, String>) -> Result<(), String> {
+    let template = Template::parse(source)?;
+    for name in template.variables() {
+        if !BUILTIN_VARS.contains(&name) && !user_vars.contains_key(name) {
+            return Err(format!("unknown template variable '{}'", name));
+        }
+    }
+    Ok(())
+}
+
+/// Every string inside a block/attachment tree, for validation.
+pub fn strings_in(value: &Value) -> Vec<&str> {
+    let mut out = Vec::new();
+    collect_strings(value, &mut out);
+    out
+}
+
+fn collect_strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => out.push(s),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, out)),
+        Value::Object(map) => map.values().for_each(|item| collect_strings(item, out)),
+        _ => {}
+    }
+}
+
+/// Render a string template, leaving it untouched if it does not parse.
+pub fn render_str(source: &str, vars: &HashMap<String, String>) -> String {
+    match Template::parse(source) {
+        Ok(template) => template.render(vars),
+        Err(_) => source.to_string(),
+    }
+}
+
+/// Render every string inside a block/attachment tree.
+pub fn render_value(value: &Value, vars: &HashMap<String, String>) -> Value {
+    match value {
+        Value::String(s) => Value::String(render_str(s, vars)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| render_value(v, vars)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), render_value(v, vars)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Built-in variables for one occurrence, overlaid on the user's variables.
+pub fn variables(
+    reminder_name: &str,
+    occurrence: &Occurrence,
+    occurrence_count: u64,
+    next: Option<&Occurrence>,
+    user_vars: &BTreeMap<String, String>,
+) -> HashMap<String, String> {
+    let mut vars: HashMap<String, String> = user_vars
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    let local = occurrence.local;
+    let builtins = [
+        ("reminder_name", reminder_name.to_string()),
+        (
+            "fire_time_local",
+            local.format("%Y-%m-%d %H:%M %Z").to_string(),
+        ),
+        (
+            "fire_time_utc",
+            occurrence.utc.format("%Y-%m-%d %H:%M UTC").to_string(),
+        ),
+        ("fire_time_iso", local.to_rfc3339()),
+        ("fire_date", local.format("%Y-%m-%d").to_string()),
+        ("fire_time", local.format("%H:%M").to_string()),
+        ("fire_weekday", local.format("%A").to_string()),
+        ("week_number", local.format("%V").to_string()),
+        ("occurrence_count", occurrence_count.to_string()),
+        (
+            "next_fire",
+            next.map(|n| n.local.format("%Y-%m-%d %H:%M %Z").to_string())
+                .unwrap_or_else(|| "never".to_string()),
+        ),
+        (
+            "next_fire_date",
+            next.map(|n| n.local.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| "never".to_string()),
+        ),
+    ];
+    for (name, value) in builtins {
+        vars.insert(name.to_string(), value);
+    }
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_placeholders_and_escapes() {
+        let template = Template::parse("Hi {{ team }}, it is {{fire_time}}.").unwrap();
+        assert_eq!(
+            template.variables().collect::<Vec<_>>(),
+            ["team", "fire_time"]
+        );
+        let escaped = Template::parse("Write {{{{name}} for {{team}}").unwrap();
+        assert_eq!(escaped.variables().collect::<Vec<_>>(), ["team"]);
+
+        assert!(Template::parse("Hi {{team")
+            .unwrap_err()
+            .contains("unclosed"));
+        for bad in ["{{}}", "{{ }}", "{{fire-time}}", "{{a b}}"] {
+            let error = Template::parse(bad).unwrap_err();
+            assert!(error.contains("invalid variable name"), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn renders_known_and_keeps_unknown_variables() {
+        let vars = HashMap::from([("team".to_string(), "Platform".to_string())]);
+        let render = |source| Template::parse(source).unwrap().render(&vars);
+        assert_eq!(render("{{team}} sync"), "Platform sync");
+        assert_eq!(render("{{ team }} / {{other}}"), "Platform / {{other}}");
+        assert_eq!(render("{{{{team}} is {{team}}"), "{{team}} is Platform");
+        assert_eq!(render("no placeholders }}"), "no placeholders }}");
+        assert_eq!(render_str("broken {{", &vars), "broken {{");
+    }
+
+    #[test]
+    fn validates_against_builtins_and_user_vars() {
+        let user = BTreeMap::from([("team".to_string(), "Platform".to_string())]);
+        assert!(validate("{{team}} on {{fire_date}}", &user).is_ok());
+        assert!(validate("{{{{not_a_var}}", &user).is_ok());
+        assert_eq!(
+            validate("{{sprint}}", &user),
+            Err("unknown template variable 'sprint'".to_string())
+        );
+        assert!(validate("{{team", &user).is_err());
+    }
+}